@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use error::{Error, Result};
+use types::{self, HashKey, LispType, LispValue};
+
+/// Rust values that can be passed to lisp.
+pub trait IntoLisp {
+    fn into_lisp(self) -> LispValue;
+}
+
+/// Rust values that can be read from lisp values, failing with a type error
+/// when the value has a different type.
+pub trait FromLisp: Sized {
+    fn from_lisp(value: &LispValue) -> Result<Self>;
+}
+
+impl IntoLisp for LispValue {
+    fn into_lisp(self) -> LispValue {
+        self
+    }
+}
+
+impl IntoLisp for () {
+    fn into_lisp(self) -> LispValue {
+        types::nil()
+    }
+}
+
+impl IntoLisp for bool {
+    fn into_lisp(self) -> LispValue {
+        types::boolean(self)
+    }
+}
+
+impl IntoLisp for i64 {
+    fn into_lisp(self) -> LispValue {
+        types::integer(self)
+    }
+}
+
+impl IntoLisp for String {
+    fn into_lisp(self) -> LispValue {
+        types::string(self)
+    }
+}
+
+impl<'a> IntoLisp for &'a str {
+    fn into_lisp(self) -> LispValue {
+        types::string(self.to_owned())
+    }
+}
+
+/// `None` is nil.
+impl<T: IntoLisp> IntoLisp for Option<T> {
+    fn into_lisp(self) -> LispValue {
+        match self {
+            Some(value) => value.into_lisp(),
+            None => types::nil(),
+        }
+    }
+}
+
+/// Vectors become lists.
+impl<T: IntoLisp> IntoLisp for Vec<T> {
+    fn into_lisp(self) -> LispValue {
+        types::list(self.into_iter().map(IntoLisp::into_lisp).collect())
+    }
+}
+
+impl<K: Into<HashKey> + Eq + Hash, V: IntoLisp> IntoLisp for HashMap<K, V> {
+    fn into_lisp(self) -> LispValue {
+        types::hash_map(self.into_iter().map(|(key, value)| (key.into(), value.into_lisp())).collect())
+    }
+}
+
+impl FromLisp for LispValue {
+    fn from_lisp(value: &LispValue) -> Result<LispValue> {
+        Ok(value.clone())
+    }
+}
+
+impl FromLisp for bool {
+    fn from_lisp(value: &LispValue) -> Result<bool> {
+        match **value {
+            LispType::Bool(b) => Ok(b),
+            _ => Err(Error::TypeError),
+        }
+    }
+}
+
+impl FromLisp for i64 {
+    fn from_lisp(value: &LispValue) -> Result<i64> {
+        match **value {
+            LispType::Integer(i) => Ok(i),
+            _ => Err(Error::TypeError),
+        }
+    }
+}
+
+impl FromLisp for String {
+    fn from_lisp(value: &LispValue) -> Result<String> {
+        match **value {
+            LispType::Strn(ref s) => Ok(s.to_owned()),
+            _ => Err(Error::TypeError),
+        }
+    }
+}
+
+/// Nil is `None`.
+impl<T: FromLisp> FromLisp for Option<T> {
+    fn from_lisp(value: &LispValue) -> Result<Option<T>> {
+        match **value {
+            LispType::Nil => Ok(None),
+            _ => Ok(Some(T::from_lisp(value)?)),
+        }
+    }
+}
+
+/// Lists and vectors, where nil is empty.
+impl<T: FromLisp> FromLisp for Vec<T> {
+    fn from_lisp(value: &LispValue) -> Result<Vec<T>> {
+        match **value {
+            LispType::Nil => Ok(vec![]),
+            LispType::List(ref values) | LispType::Vector(ref values) => values.iter().map(T::from_lisp).collect(),
+            _ => Err(Error::TypeError),
+        }
+    }
+}
+
+/// Hash maps, where nil is empty.
+impl<K: FromLisp + Eq + Hash, V: FromLisp> FromLisp for HashMap<K, V> {
+    fn from_lisp(value: &LispValue) -> Result<HashMap<K, V>> {
+        match **value {
+            LispType::Nil => Ok(HashMap::new()),
+            LispType::HashMap(ref data) => {
+                data.iter().map(|(key, value)| Ok((K::from_lisp(&key.to_value())?, V::from_lisp(value)?))).collect()
+            },
+            _ => Err(Error::TypeError),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use error::{Error, Result};
+use types::{self, CallContext, LispType, LispValue};
+
+pub fn atom(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    Ok(types::atom(args[0].clone()))
+}
+
+pub fn deref(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Atom(ref atom) => Ok(atom.value.borrow().clone()),
+        _ => Err(Error::TypeError),
+    }
+}
+
+pub fn reset(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Atom(ref atom) => {
+            *atom.value.borrow_mut() = args[1].clone();
+
+            Ok(args[1].clone())
+        },
+        _ => Err(Error::TypeError),
+    }
+}
+
+/// `(swap! atom f args...)` sets the atom to `(f value args...)` and returns
+/// the new value.
+pub fn swap(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Atom(ref atom) => {
+            let mut arguments = vec![atom.value.borrow().clone()];
+            arguments.extend_from_slice(&args[2..]);
+
+            let result = ctx.apply(&args[1], &arguments)?;
+            *atom.value.borrow_mut() = result.clone();
+
+            Ok(result)
+        },
+        _ => Err(Error::TypeError),
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use error::{Error, Result};
+use json;
+use printer::Printer;
+use types::{CallContext, LispValue, LispType, string};
+
+/// `(pipe command args... input)` runs a command with `input` on its standard
+/// input and returns what it writes to its standard output. Any non-zero exit
+/// status is an error, unless the command is preceded by `:no-match`, which
+/// accepts the status 1 that `grep` uses when nothing matched.
+///
+/// The input comes last so that `->>` can feed values to commands. Strings are
+/// written as they are, lists and vectors one element per line, and hash maps
+/// as JSON, which is also how maps inside lists are written.
+pub fn pipe(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let (input, words) = match args.split_last() {
+        Some(split) => split,
+        None => return Err(Error::TypeError),
+    };
+
+    let no_match = match words.first() {
+        Some(word) => **word == LispType::Keyword("no-match".to_owned()),
+        None => false,
+    };
+    let words = if no_match { &words[1..] } else { words };
+
+    let mut words = words.iter().map(|word| match **word {
+        LispType::Strn(ref word) => Ok(word.to_owned()),
+        _ => Err(Error::TypeError),
+    }).collect::<Result<Vec<String>>>()?.into_iter();
+
+    let name = words.next().ok_or(Error::TypeError)?;
+    let bytes = serialize(input)?.into_bytes();
+
+    let mut child = Command::new(&name)
+        .args(words)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => Error::CommandNotFound(name.clone()),
+            _ => Error::IoError(err),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || stdin.write_all(&bytes));
+    }
+
+    let output = child.wait_with_output()?;
+
+    let failed = match output.status.code() {
+        Some(0) => false,
+        Some(1) => !no_match,
+        _ => true,
+    };
+
+    if failed {
+        let status = output.status.code().map_or_else(|| "a signal".to_owned(), |code| format!("status {}", code));
+
+        return Err(Error::BuiltinError("pipe".to_owned(), format!("{} exited with {}", name, status)));
+    }
+
+    Ok(string(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+fn serialize(value: &LispValue) -> Result<String> {
+    match **value {
+        LispType::Nil => Ok(String::new()),
+        LispType::List(ref values) | LispType::Vector(ref values) => {
+            let mut text = String::new();
+
+            for value in values {
+                text.push_str(&serialize_line(value)?);
+                text.push('\n');
+            }
+
+            Ok(text)
+        },
+        _ => serialize_line(value),
+    }
+}
+
+fn serialize_line(value: &LispValue) -> Result<String> {
+    match **value {
+        LispType::List(_) | LispType::Vector(_) | LispType::HashMap(_) => json::to_json(value),
+        _ => Ok(Printer::new().print(value, false)),
+    }
+}
@@ -0,0 +1,24 @@
+use error::{Error, Result};
+use json;
+use types::{CallContext, LispValue, LispType, list, string};
+
+pub fn to_json(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    Ok(string(json::to_json(&args[0])?))
+}
+
+pub fn from_json(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Strn(ref input) => json::from_json(input),
+        _ => Err(Error::TypeError),
+    }
+}
+
+/// The lines of a string, like the output of a command, without their line
+/// endings.
+pub fn lines(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Strn(ref input) => Ok(list(input.lines().map(|line| string(line.to_owned())).collect())),
+        LispType::Nil => Ok(list(vec![])),
+        _ => Err(Error::TypeError),
+    }
+}
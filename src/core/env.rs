@@ -1,37 +1,101 @@
 use std::collections::HashMap;
 
 use env::{Env, env_new, env_set};
-use types::{LispValue, native_function};
+use error::Result;
+use types::{Arity, CallContext, LispValue, native_function};
+use types::Arity::{Exactly, AtLeast, Between};
 use core::math;
 use core::file;
 use core::reader;
 use core::list;
 use core::string;
+use core::keyword;
+use core::atom;
 use core::hash;
 use core::printer;
 use core::environment;
+use core::history;
+use core::frecency;
+use core::module;
+use core::data;
+use core::command;
+
+/// Binds `name` to a native function that fails with an arity error when it
+/// gets a number of arguments that doesn't fit `arity`.
+fn define(ns: &mut HashMap<&'static str, LispValue>, name: &'static str, arity: Arity, function: fn(&[LispValue], &CallContext) -> Result<LispValue>) {
+    ns.insert(name, native_function(move |args, ctx| {
+        arity.check(name, args.len())?;
+
+        function(args, ctx)
+    }));
+}
 
 fn ns() -> HashMap<&'static str, LispValue> {
     let mut ns = HashMap::new();
 
-    ns.insert("+", native_function(math::add));
-    ns.insert("-", native_function(math::sub));
+    define(&mut ns, "+", Exactly(2), math::add);
+    define(&mut ns, "-", Exactly(2), math::sub);
+
+    define(&mut ns, "slurp", Exactly(1), file::slurp);
+    define(&mut ns, "read", Exactly(1), reader::read);
+    define(&mut ns, "ls-data", Between(0, 1), file::ls_data);
+    define(&mut ns, "dir?", Exactly(1), file::is_dir);
+
+    define(&mut ns, "to-json", Exactly(1), data::to_json);
+    define(&mut ns, "from-json", Exactly(1), data::from_json);
+    define(&mut ns, "lines", Exactly(1), data::lines);
+    define(&mut ns, "pipe", AtLeast(2), command::pipe);
+
+    define(&mut ns, "list", AtLeast(0), list::list_c);
+    define(&mut ns, "vector", AtLeast(0), list::vector_c);
+    define(&mut ns, "first", Exactly(1), list::first);
+    define(&mut ns, "rest", Exactly(1), list::rest);
+    define(&mut ns, "nth", Exactly(2), list::nth);
+    define(&mut ns, "count", Exactly(1), list::count);
+    define(&mut ns, "empty?", Exactly(1), list::empty);
+    define(&mut ns, "cons", Exactly(2), list::cons);
+    define(&mut ns, "concat", AtLeast(0), list::concat);
+    define(&mut ns, "reverse", Exactly(1), list::reverse);
+    define(&mut ns, "sort", Exactly(1), list::sort);
+    define(&mut ns, "range", Between(1, 3), list::range);
+    define(&mut ns, "map", Exactly(2), list::map);
+    define(&mut ns, "filter", Exactly(2), list::filter);
+    define(&mut ns, "reduce", Between(2, 3), list::reduce);
+    define(&mut ns, "apply", AtLeast(2), list::apply);
+
+    define(&mut ns, "string-append", AtLeast(0), string::string_append);
+
+    define(&mut ns, "keyword", Exactly(1), keyword::keyword);
+    define(&mut ns, "name", Exactly(1), keyword::name);
+
+    define(&mut ns, "hash", AtLeast(0), hash::hash);
+    define(&mut ns, "hash-set", Exactly(3), hash::hash_set);
+    define(&mut ns, "get", Between(2, 3), hash::get);
+    define(&mut ns, "contains?", Exactly(2), hash::contains);
+    define(&mut ns, "keys", Exactly(1), hash::keys);
+    define(&mut ns, "vals", Exactly(1), hash::vals);
+    define(&mut ns, "dissoc", AtLeast(1), hash::dissoc);
+    define(&mut ns, "merge", AtLeast(0), hash::merge);
+    define(&mut ns, "update", AtLeast(3), hash::update);
 
-    ns.insert("slurp", native_function(file::slurp));
-    ns.insert("read", native_function(reader::read));
+    define(&mut ns, "atom", Exactly(1), atom::atom);
+    define(&mut ns, "deref", Exactly(1), atom::deref);
+    define(&mut ns, "reset!", Exactly(2), atom::reset);
+    define(&mut ns, "swap!", AtLeast(2), atom::swap);
 
-    ns.insert("list", native_function(list::list_c));
+    define(&mut ns, "display", Exactly(1), printer::display);
+    define(&mut ns, "write", Exactly(1), printer::write);
 
-    ns.insert("string-append", native_function(string::string_append));
+    define(&mut ns, "env-get", Exactly(1), environment::env_get);
+    define(&mut ns, "env-set", Exactly(2), environment::env_set);
 
-    ns.insert("hash", native_function(hash::hash));
-    ns.insert("hash-set", native_function(hash::hash_set));
+    define(&mut ns, "history", Exactly(0), history::history);
 
-    ns.insert("display", native_function(printer::display));
-    ns.insert("write", native_function(printer::write));
+    define(&mut ns, "dir-history", Exactly(0), frecency::dir_history);
+    define(&mut ns, "dir-rank", AtLeast(0), frecency::dir_rank);
 
-    ns.insert("env-get", native_function(environment::env_get));
-    ns.insert("env-set", native_function(environment::env_set));
+    ns.insert("require", module::require());
+    ns.insert("*load-path*", module::default_load_path());
 
     ns
 }
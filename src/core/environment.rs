@@ -1,9 +1,9 @@
 use std;
 
 use error::{Error, Result};
-use types::{LispValue, LispType, string, nil};
+use types::{CallContext, LispValue, LispType, string, nil};
 
-pub fn env_get(args: &[LispValue]) -> Result<LispValue> {
+pub fn env_get(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Strn(ref name) => {
             match std::env::var(name) {
@@ -15,7 +15,7 @@ pub fn env_get(args: &[LispValue]) -> Result<LispValue> {
     }
 }
 
-pub fn env_set(args: &[LispValue]) -> Result<LispValue> {
+pub fn env_set(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Strn(ref name) => {
             match *args[1] {
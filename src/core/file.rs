@@ -1,10 +1,14 @@
-use std::fs::File;
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata};
 use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
 
 use error::{Error, Result};
-use types::{LispValue, LispType, string};
+use types::{CallContext, HashKey, LispValue, LispType, boolean, hash_map, integer, list, string};
 
-pub fn slurp(args: &[LispValue]) -> Result<LispValue> {
+pub fn slurp(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Strn(ref value) => {
             let mut buffer = String::new();
@@ -15,3 +19,95 @@ pub fn slurp(args: &[LispValue]) -> Result<LispValue> {
         _ => Err(Error::TypeError),
     }
 }
+
+/// `(ls-data)` or `(ls-data dir)` lists a directory as hash maps with the
+/// `:name`, `:path`, `:type`, `:size`, `:mtime` and `:permissions` of each
+/// entry, sorted by name.
+pub fn ls_data(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let dir = match args.first() {
+        None => ".".to_owned(),
+        Some(arg) => match **arg {
+            LispType::Strn(ref dir) => dir.to_owned(),
+            _ => return Err(Error::TypeError),
+        },
+    };
+
+    let mut entries = vec![];
+
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let metadata = fs::symlink_metadata(entry.path())?;
+
+        entries.push((entry.file_name().to_string_lossy().into_owned(), entry.path(), metadata));
+    }
+
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(list(entries.into_iter().map(|(name, path, metadata)| entry_data(name, &path, &metadata)).collect()))
+}
+
+fn entry_data(name: String, path: &Path, metadata: &Metadata) -> LispValue {
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_file() {
+        "file"
+    } else {
+        "other"
+    };
+
+    let mtime = metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs() as i64);
+
+    let mut data = HashMap::new();
+
+    data.insert(HashKey::Keyword("name".to_owned()), string(name));
+    data.insert(HashKey::Keyword("path".to_owned()), string(path.to_string_lossy().into_owned()));
+    data.insert(HashKey::Keyword("type".to_owned()), string(kind.to_owned()));
+    data.insert(HashKey::Keyword("size".to_owned()), integer(metadata.len() as i64));
+    data.insert(HashKey::Keyword("mtime".to_owned()), integer(mtime));
+    data.insert(HashKey::Keyword("permissions".to_owned()), string(permissions(metadata.permissions().mode())));
+
+    hash_map(data)
+}
+
+/// The permission bits of `mode` as `ls` shows them, like `rwxr-xr-x`.
+fn permissions(mode: u32) -> String {
+    let flags = ['r', 'w', 'x'];
+
+    (0..9).map(|bit| {
+        if mode & (0o400 >> bit) != 0 {
+            flags[bit % 3]
+        } else {
+            '-'
+        }
+    }).collect()
+}
+
+/// Whether a path, or an entry from `ls-data`, is a directory.
+pub fn is_dir(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Strn(ref path) => Ok(boolean(Path::new(path).is_dir())),
+        LispType::HashMap(ref data) => {
+            let kind = data.get(&HashKey::Keyword("type".to_owned()));
+
+            Ok(boolean(kind.is_some_and(|kind| **kind == LispType::Strn("dir".to_owned()))))
+        },
+        _ => Err(Error::TypeError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_permissions() {
+        assert_eq!(permissions(0o755), "rwxr-xr-x");
+        assert_eq!(permissions(0o640), "rw-r-----");
+        assert_eq!(permissions(0o100644), "rw-r--r--");
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use frecency::{self, Database};
+use error::{Error, Result};
+use types::{CallContext, HashKey, LispType, LispValue, list, hash_map, integer, string};
+
+pub fn dir_history(_: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let now = frecency::now();
+    let dirs = Database::new().rank(&[], now)?;
+
+    let values = dirs.into_iter().map(|dir| {
+        let mut data = HashMap::new();
+
+        data.insert(HashKey::from("score"), integer(dir.frecency(now).round() as i64));
+        data.insert(HashKey::from("timestamp"), integer(dir.last_access as i64));
+        data.insert(HashKey::from("path"), string(dir.path));
+
+        hash_map(data)
+    }).collect();
+
+    Ok(list(values))
+}
+
+pub fn dir_rank(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut keywords = vec![];
+
+    for arg in args {
+        match **arg {
+            LispType::Strn(ref query) => keywords.extend(query.split_whitespace().map(|word| word.to_owned())),
+            _ => return Err(Error::TypeError),
+        }
+    }
+
+    let dirs = Database::new().rank(&keywords, frecency::now())?;
+
+    Ok(list(dirs.into_iter().map(|dir| string(dir.path)).collect()))
+}
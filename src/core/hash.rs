@@ -1,39 +1,99 @@
 use std::collections::HashMap;
 
 use error::{Error, Result};
-use types::{LispType, LispValue, hash_map};
+use types::{self, CallContext, HashKey, LispType, LispValue, hash_map, list, boolean, nil};
+
+/// Entries of a hash map, where nil is an empty map.
+fn entries(value: &LispValue) -> Result<HashMap<HashKey, LispValue>> {
+    match **value {
+        LispType::Nil => Ok(HashMap::new()),
+        LispType::HashMap(ref data) => Ok(data.clone()),
+        _ => Err(Error::TypeError),
+    }
+}
+
+pub fn hash(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    if args.len() % 2 != 0 {
+        return Err(Error::TypeError);
+    }
 
-pub fn hash(args: &[LispValue]) -> Result<LispValue> {
     let mut data = HashMap::new();
 
     for entry in args.chunks(2) {
-        let key = match *entry[0] {
-            LispType::Strn(ref value) => value.to_owned(),
-            _ => return Err(Error::TypeError),
-        };
-        let value = entry[1].clone();
-        data.insert(key, value);
+        data.insert(HashKey::from_value(&entry[0])?, entry[1].clone());
+    }
+
+    Ok(hash_map(data))
+}
+
+pub fn hash_set(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut data = entries(&args[0])?;
+
+    data.insert(HashKey::from_value(&args[1])?, args[2].clone());
+
+    Ok(hash_map(data))
+}
+
+/// `(get map key)` or `(get map key default)`.
+pub fn get(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let data = entries(&args[0])?;
+    let default = args.get(2).cloned().unwrap_or_else(nil);
+
+    Ok(data.get(&HashKey::from_value(&args[1])?).cloned().unwrap_or(default))
+}
+
+pub fn contains(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let data = entries(&args[0])?;
+
+    Ok(boolean(data.contains_key(&HashKey::from_value(&args[1])?)))
+}
+
+/// Keys in the order they are printed.
+pub fn keys(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let data = entries(&args[0])?;
+
+    Ok(list(types::sorted_entries(&data).into_iter().map(|(key, _)| key.to_value()).collect()))
+}
+
+/// Values in the order of their keys.
+pub fn vals(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let data = entries(&args[0])?;
+
+    Ok(list(types::sorted_entries(&data).into_iter().map(|(_, value)| value.clone()).collect()))
+}
+
+pub fn dissoc(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut data = entries(&args[0])?;
+
+    for key in &args[1..] {
+        data.remove(&HashKey::from_value(key)?);
     }
 
     Ok(hash_map(data))
 }
 
-pub fn hash_set(args: &[LispValue]) -> Result<LispValue> {
-    let hash = args[0].clone();
-    let key = args[1].clone();
-    let value = args[2].clone();
+/// Entries of all the maps, where later maps win.
+pub fn merge(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut data = HashMap::new();
+
+    for arg in args {
+        data.extend(entries(arg)?);
+    }
+
+    Ok(hash_map(data))
+}
 
-    let mut data = match *hash {
-        LispType::HashMap(ref data) => data.clone(),
-        _ => return Err(Error::TypeError),
-    };
+/// `(update map key f args...)` sets `key` to `(f value args...)`, where value
+/// is nil when the key is missing.
+pub fn update(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    let mut data = entries(&args[0])?;
+    let key = HashKey::from_value(&args[1])?;
 
-    let key_str = match *key {
-        LispType::Strn(ref value) => value.to_owned(),
-        _ => return Err(Error::TypeError),
-    };
+    let mut arguments = vec![data.get(&key).cloned().unwrap_or_else(nil)];
+    arguments.extend_from_slice(&args[3..]);
 
-    data.insert(key_str, value);
+    let value = ctx.apply(&args[2], &arguments)?;
+    data.insert(key, value);
 
     Ok(hash_map(data))
 }
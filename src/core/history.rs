@@ -0,0 +1,21 @@
+use std::collections::HashMap;
+
+use history::History;
+use error::Result;
+use types::{CallContext, HashKey, LispValue, list, hash_map, integer, string};
+
+pub fn history(_: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let entries = History::new().load()?;
+
+    let values = entries.into_iter().map(|entry| {
+        let mut data = HashMap::new();
+
+        data.insert(HashKey::from("timestamp"), integer(entry.timestamp as i64));
+        data.insert(HashKey::from("cwd"), string(entry.cwd));
+        data.insert(HashKey::from("command"), string(entry.command));
+
+        hash_map(data)
+    }).collect();
+
+    Ok(list(values))
+}
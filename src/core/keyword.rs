@@ -0,0 +1,19 @@
+use error::{Error, Result};
+use types::{CallContext, LispType, LispValue, keyword as keyword_t, string};
+
+pub fn keyword(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Keyword(_) => Ok(args[0].clone()),
+        LispType::Strn(ref name) | LispType::Symbol(ref name) => Ok(keyword_t(name.to_owned())),
+        _ => Err(Error::TypeError),
+    }
+}
+
+/// Name of a keyword or symbol, without the colon.
+pub fn name(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    match *args[0] {
+        LispType::Strn(_) => Ok(args[0].clone()),
+        LispType::Keyword(ref name) | LispType::Symbol(ref name) => Ok(string(name.to_owned())),
+        _ => Err(Error::TypeError),
+    }
+}
@@ -1,6 +1,214 @@
-use error::Result;
-use types::{LispValue, list};
+use error::{Error, Result};
+use types::{self, CallContext, LispType, LispValue, list, integer, boolean, nil};
 
-pub fn list_c(args: &[LispValue]) -> Result<LispValue> {
+/// Elements of a list or vector, where nil is an empty collection.
+fn items(value: &LispValue) -> Result<&[LispValue]> {
+    match **value {
+        LispType::Nil => Ok(&[]),
+        LispType::List(ref values) | LispType::Vector(ref values) => Ok(values),
+        _ => Err(Error::TypeError),
+    }
+}
+
+fn index(value: &LispValue) -> Result<i64> {
+    match **value {
+        LispType::Integer(i) => Ok(i),
+        _ => Err(Error::TypeError),
+    }
+}
+
+pub fn list_c(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     Ok(list(args.to_vec()))
 }
+
+pub fn vector_c(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    Ok(types::vector(args.to_vec()))
+}
+
+pub fn first(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    Ok(items(&args[0])?.first().cloned().unwrap_or_else(nil))
+}
+
+pub fn rest(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let values = items(&args[0])?;
+
+    Ok(list(values.iter().skip(1).cloned().collect()))
+}
+
+pub fn nth(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let values = items(&args[0])?;
+    let i = index(&args[1])?;
+
+    if i < 0 || i as usize >= values.len() {
+        return Err(Error::IndexOutOfRange(i));
+    }
+
+    Ok(values[i as usize].clone())
+}
+
+pub fn count(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let count = match *args[0] {
+        LispType::Strn(ref value) => value.chars().count(),
+        LispType::HashMap(ref data) => data.len(),
+        _ => items(&args[0])?.len(),
+    };
+
+    Ok(integer(count as i64))
+}
+
+pub fn empty(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let empty = match *args[0] {
+        LispType::Strn(ref value) => value.is_empty(),
+        LispType::HashMap(ref data) => data.is_empty(),
+        _ => items(&args[0])?.is_empty(),
+    };
+
+    Ok(boolean(empty))
+}
+
+pub fn cons(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut values = vec![args[0].clone()];
+    values.extend_from_slice(items(&args[1])?);
+
+    Ok(list(values))
+}
+
+pub fn concat(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut values = vec![];
+
+    for arg in args {
+        values.extend_from_slice(items(arg)?);
+    }
+
+    Ok(list(values))
+}
+
+pub fn reverse(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    Ok(list(items(&args[0])?.iter().rev().cloned().collect()))
+}
+
+/// Sorts integers or strings, which can't be mixed.
+pub fn sort(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let mut values = items(&args[0])?.to_vec();
+
+    let all_integers = values.iter().all(|value| match **value {
+        LispType::Integer(_) => true,
+        _ => false,
+    });
+    let all_strings = values.iter().all(|value| match **value {
+        LispType::Strn(_) => true,
+        _ => false,
+    });
+
+    if !all_integers && !all_strings {
+        return Err(Error::TypeError);
+    }
+
+    values.sort_by(|a, b| {
+        match (&**a, &**b) {
+            (&LispType::Integer(a), &LispType::Integer(b)) => a.cmp(&b),
+            (&LispType::Strn(ref a), &LispType::Strn(ref b)) => a.cmp(b),
+            _ => unreachable!(),
+        }
+    });
+
+    Ok(list(values))
+}
+
+/// Most elements `range` builds, so that a huge range is an error instead of
+/// running out of memory.
+const MAX_RANGE: i128 = 1 << 20;
+
+/// `(range end)`, `(range start end)` or `(range start end step)`, excluding `end`.
+pub fn range(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
+    let (start, end, step) = match args.len() {
+        1 => (0, index(&args[0])?, 1),
+        2 => (index(&args[0])?, index(&args[1])?, 1),
+        3 => (index(&args[0])?, index(&args[1])?, index(&args[2])?),
+        _ => return Err(Error::TypeError),
+    };
+
+    if step == 0 {
+        return Err(Error::TypeError);
+    }
+
+    if (i128::from(end) - i128::from(start)) / i128::from(step) > MAX_RANGE {
+        return Err(Error::IndexOutOfRange(end));
+    }
+
+    let mut values = vec![];
+    let mut value = start;
+
+    while (step > 0 && value < end) || (step < 0 && value > end) {
+        values.push(integer(value));
+
+        value = match value.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(list(values))
+}
+
+pub fn map(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    let mut values = vec![];
+
+    for value in items(&args[1])? {
+        values.push(ctx.apply(&args[0], &[value.clone()])?);
+    }
+
+    Ok(list(values))
+}
+
+/// Keeps the elements for which the function returns anything but nil or false.
+pub fn filter(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    let mut values = vec![];
+
+    for value in items(&args[1])? {
+        if types::is_truthy(&ctx.apply(&args[0], &[value.clone()])?) {
+            values.push(value.clone());
+        }
+    }
+
+    Ok(list(values))
+}
+
+/// `(reduce f coll)` or `(reduce f init coll)`.
+///
+/// Without an initial value the first element is used, and an empty
+/// collection gives the result of calling the function with no arguments.
+pub fn reduce(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    let function = &args[0];
+
+    let (init, values) = if args.len() > 2 {
+        (args[1].clone(), items(&args[2])?)
+    } else {
+        match items(&args[1])?.split_first() {
+            Some((first, rest)) => (first.clone(), rest),
+            None => return ctx.apply(function, &[]),
+        }
+    };
+
+    let mut result = init;
+
+    for value in values {
+        result = ctx.apply(function, &[result, value.clone()])?;
+    }
+
+    Ok(result)
+}
+
+/// Calls a function with the given arguments followed by the elements of the
+/// last one.
+pub fn apply(args: &[LispValue], ctx: &CallContext) -> Result<LispValue> {
+    let (last, middle) = match args[1..].split_last() {
+        Some(split) => split,
+        None => return Err(Error::TypeError),
+    };
+
+    let mut arguments = middle.to_vec();
+    arguments.extend_from_slice(items(last)?);
+
+    ctx.apply(&args[0], &arguments)
+}
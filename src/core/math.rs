@@ -1,13 +1,13 @@
 use error::{Error, Result};
-use types::{LispType, LispValue};
+use types::{CallContext, LispType, LispValue};
 use types;
 
-pub fn add(args: &[LispValue]) -> Result<LispValue> {
+pub fn add(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Integer(i1) => {
             match *args[1] {
                 LispType::Integer(i2) => {
-                    Ok(types::integer(i1 + i2))
+                    i1.checked_add(i2).map(types::integer).ok_or(Error::IntegerOverflow)
                 }
                 _ => {
                     Err(Error::TypeError)
@@ -20,12 +20,12 @@ pub fn add(args: &[LispValue]) -> Result<LispValue> {
     }
 }
 
-pub fn sub(args: &[LispValue]) -> Result<LispValue> {
+pub fn sub(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Integer(i1) => {
             match *args[1] {
                 LispType::Integer(i2) => {
-                    Ok(types::integer(i1 - i2))
+                    i1.checked_sub(i2).map(types::integer).ok_or(Error::IntegerOverflow)
                 }
                 _ => {
                     Err(Error::TypeError)
@@ -3,8 +3,15 @@ mod file;
 mod reader;
 mod list;
 mod string;
+mod keyword;
+mod atom;
 mod printer;
 mod hash;
 mod environment;
+mod history;
+mod frecency;
+mod module;
+mod data;
+mod command;
 
 pub mod env;
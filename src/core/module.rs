@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use env::{Env, env_new, env_get, env_set, env_root};
+use error::{Error, Result};
+use lisp::engine::Engine as LispEngine;
+use paths;
+use types::{self, Arity, HashKey, LispType, LispValue};
+
+/// Modules by canonical path: their namespace once loaded, None while loading.
+type Registry = Rc<RefCell<HashMap<PathBuf, Option<LispValue>>>>;
+
+/// Directories searched by `require`, in order.
+pub fn default_load_path() -> LispValue {
+    let dirs = paths::config_dir().into_iter().map(|mut dir| {
+        dir.push("lib");
+        types::string(dir.to_string_lossy().into_owned())
+    }).collect();
+
+    types::list(dirs)
+}
+
+/// The `require` native, sharing loaded modules between every call.
+///
+/// `(require "git-helpers")` loads `git-helpers.lish` from the first directory
+/// in `*load-path*` that has it, or the file itself when the name is a path,
+/// relative to the module calling `require` or else the current directory,
+/// and binds its namespace to `git-helpers`, or to another name with
+/// `(require "git-helpers" :as "git")`. Members are accessed as `git/branch`.
+///
+/// Each module is evaluated once, in its own environment, and its namespace
+/// is a hash map of the names it lists with `export`. Modules see the core
+/// functions and the standard library, and the `*load-path*` of the code
+/// requiring them, but not the other globals of the user.
+pub fn require() -> LispValue {
+    let registry: Registry = Rc::new(RefCell::new(HashMap::new()));
+
+    types::native_function(move |args, ctx| {
+        Arity::Between(1, 3).check("require", args.len())?;
+
+        let name = string_arg(&args[0])?;
+
+        let binding = match args.get(1) {
+            None => Path::new(&name).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| name.clone()),
+            Some(option) if **option == LispType::Keyword("as".to_owned()) => string_arg(args.get(2).ok_or(Error::TypeError)?)?,
+            Some(_) => return Err(Error::TypeError),
+        };
+
+        let path = resolve(&name, ctx.env)?;
+        let namespace = load(&registry, &name, &path, ctx.env)?;
+
+        env_set(ctx.env, &binding, namespace.clone());
+
+        Ok(namespace)
+    })
+}
+
+fn string_arg(value: &LispValue) -> Result<String> {
+    match **value {
+        LispType::Strn(ref s) => Ok(s.to_owned()),
+        _ => Err(Error::TypeError),
+    }
+}
+
+fn not_found(name: &str) -> Error {
+    Error::RequireError(name.to_owned(), "module not found".to_owned())
+}
+
+/// Canonical path of module `name`.
+fn resolve(name: &str, env: &Env) -> Result<PathBuf> {
+    if name.contains('/') || name.starts_with('.') || name.starts_with('~') {
+        let path = paths::expand_home(name);
+
+        let path = match env_get(env, "*module-dir*").ok() {
+            Some(ref dir) if path.is_relative() => Path::new(&string_arg(dir)?).join(path),
+            _ => path,
+        };
+
+        return path.canonicalize().map_err(|_| not_found(name));
+    }
+
+    let load_path = match env_get(env, "*load-path*").ok() {
+        Some(value) => value,
+        None => return Err(not_found(name)),
+    };
+
+    let dirs = match *load_path {
+        LispType::Nil => return Err(not_found(name)),
+        LispType::List(ref dirs) | LispType::Vector(ref dirs) => dirs.clone(),
+        _ => return Err(Error::TypeError),
+    };
+
+    let file_name = if name.ends_with(".lish") { name.to_owned() } else { format!("{}.lish", name) };
+
+    for dir in dirs {
+        let mut path = paths::expand_home(&string_arg(&dir)?);
+        path.push(&file_name);
+
+        if path.is_file() {
+            return path.canonicalize().map_err(Error::from);
+        }
+    }
+
+    Err(not_found(name))
+}
+
+fn load(registry: &Registry, name: &str, path: &Path, env: &Env) -> Result<LispValue> {
+    match registry.borrow().get(path).cloned() {
+        Some(Some(namespace)) => return Ok(namespace),
+        Some(None) => return Err(Error::RequireError(name.to_owned(), "circular require".to_owned())),
+        None => {},
+    }
+
+    registry.borrow_mut().insert(path.to_path_buf(), None);
+
+    let result = evaluate(path, env);
+
+    match result {
+        Ok(ref namespace) => registry.borrow_mut().insert(path.to_path_buf(), Some(namespace.clone())),
+        Err(_) => registry.borrow_mut().remove(path),
+    };
+
+    result
+}
+
+/// Evaluates the module at `path` and collects its exports.
+fn evaluate(path: &Path, env: &Env) -> Result<LispValue> {
+    let mut buffer = String::new();
+    File::open(path)?.read_to_string(&mut buffer)?;
+
+    let module_env = env_new(Some(env_root(env)));
+    env_set(&module_env, "*exports*", types::list(vec![]));
+
+    if let Ok(load_path) = env_get(env, "*load-path*") {
+        env_set(&module_env, "*load-path*", load_path);
+    }
+
+    if let Some(dir) = path.parent() {
+        env_set(&module_env, "*module-dir*", types::string(dir.to_string_lossy().into_owned()));
+    }
+
+    LispEngine::new().run_all(&buffer, module_env.clone())?;
+
+    let exports = env_get(&module_env, "*exports*")?;
+    let names = match *exports {
+        LispType::List(ref names) => names.clone(),
+        _ => return Err(Error::TypeError),
+    };
+
+    let mut namespace = HashMap::new();
+
+    for name in names {
+        let name = string_arg(&name)?;
+        let value = env_get(&module_env, &name)?;
+
+        namespace.insert(HashKey::from(name), value);
+    }
+
+    Ok(types::hash_map(namespace))
+}
@@ -1,8 +1,8 @@
 use printer::Printer;
 use error::{Result};
-use types::{LispValue, string};
+use types::{CallContext, LispValue, string};
 
-pub fn display(args: &[LispValue]) -> Result<LispValue> {
+pub fn display(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     let printer = Printer::new();
 
     let arg = args[0].clone();
@@ -12,7 +12,7 @@ pub fn display(args: &[LispValue]) -> Result<LispValue> {
     Ok(string(result))
 }
 
-pub fn write(args: &[LispValue]) -> Result<LispValue> {
+pub fn write(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     let printer = Printer::new();
 
     let arg = args[0].clone();
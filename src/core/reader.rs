@@ -1,9 +1,9 @@
 use lisp::lexer::tokenize;
 use lisp::parser::Parser;
 use error::{Error, Result};
-use types::{LispValue, LispType};
+use types::{CallContext, LispValue, LispType};
 
-pub fn read(args: &[LispValue]) -> Result<LispValue> {
+pub fn read(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     match *args[0] {
         LispType::Strn(ref value) => {
             let mut parser = Parser::new();
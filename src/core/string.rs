@@ -1,8 +1,8 @@
 use printer::Printer;
 use error::{Result};
-use types::{LispValue, string};
+use types::{CallContext, LispValue, string};
 
-pub fn string_append(args: &[LispValue]) -> Result<LispValue> {
+pub fn string_append(args: &[LispValue], _: &CallContext) -> Result<LispValue> {
     let printer = Printer::new();
 
     let strings: Vec<_> = args.iter().map(|arg| printer.print(arg, false)).collect();
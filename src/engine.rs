@@ -1,82 +1,132 @@
 use std::fs::File;
 use std::io::Read;
-use std::env;
-use std::env::home_dir;
+use std::path::Path;
 
-use shell::engine::Engine as ShellEngine;
+use shell::engine::{self as shell_engine, Engine as ShellEngine};
+use shell::state::State;
 use lisp::engine::Engine as LispEngine;
-use error::Result;
-use env::Env;
+use error::{Error, Result};
+use readliner::is_complete_lisp;
+use env::{Env, env_new, env_get, env_set, env_root};
 use core;
-use types::LispValue;
+use stdlib;
+use convert::IntoLisp;
+use lisp::evaluator::Evaluator;
+use types::{self, LispValue};
 
 pub struct Engine {
     lisp_engine: LispEngine,
     shell_engine: ShellEngine,
+    /// The global environment of the user, whose outer environment has the
+    /// core functions and the standard library.
     env: Env,
 }
 
 impl Engine {
     pub fn new() -> Engine {
         let core_env = core::env::create();
+        let shell_engine = ShellEngine::new();
+
+        shell_engine.bind(&core_env);
 
         Engine {
             lisp_engine: LispEngine::new(),
-            shell_engine: ShellEngine::new(),
-            env: core_env,
+            shell_engine: shell_engine,
+            env: env_new(Some(core_env)),
         }
     }
 
+    pub fn env(&self) -> Env {
+        self.env.clone()
+    }
+
     pub fn run(&mut self, input: &str) -> Result<Option<LispValue>> {
-        let first_char = input.chars().next();
-
-        match first_char {
-            None => Ok(None),
-            Some(c) => {
-                if c == '(' {
-                    Ok(Some(self.lisp_engine.run(input, self.env.clone())?))
-                } else {
-                    self.shell_engine.run(input, self.env.clone())?;
-
-                    Ok(None)
-                }
-            }
-        }
+        run_line(input, &self.env, self.shell_engine.state())
+    }
+
+    /// Whether shell commands report their errors as JSON objects.
+    pub fn set_json_errors(&mut self, json_errors: bool) {
+        self.shell_engine.state().json_errors = json_errors;
+    }
+
+    /// Binds `name` to `value` in the global environment.
+    pub fn define<V: IntoLisp>(&mut self, name: &str, value: V) {
+        env_set(&self.env, name, value.into_lisp());
     }
 
+    /// Binds `name` to a native function implemented by `function`, which gets
+    /// the evaluated arguments.
+    pub fn register_fn<F, R>(&mut self, name: &str, function: F)
+        where F: Fn(&[LispValue]) -> Result<R> + 'static, R: IntoLisp {
+        self.define(name, types::native_function(move |args, _| Ok(function(args)?.into_lisp())));
+    }
+
+    /// Evaluates the lisp forms in `input`, returning the value of the last one.
+    pub fn eval_str(&mut self, input: &str) -> Result<LispValue> {
+        LispEngine::new().run_all(input, self.env.clone())
+    }
+
+    /// Calls the function bound to `name` with `args`.
+    pub fn call(&mut self, name: &str, args: &[LispValue]) -> Result<LispValue> {
+        let function = env_get(&self.env, name)?;
+
+        Evaluator::new().apply_function(function, args, &self.env)
+    }
+
+    /// Loads the standard library embedded in the binary, taking modules from
+    /// `LISH_STDLIB_DIR` instead when it is set.
     pub fn load_standard_library(&mut self) -> Result<()> {
-        let exe_path = env::current_exe()?;
-        let mut dir = match exe_path.parent() {
-            Some(path) => path.to_path_buf(),
-            None => panic!("cant determine exe directory"),
-        };
+        let dir = stdlib::override_dir();
 
-        dir.push("stdlib");
-        dir.push("stdlib.lish");
+        self.load_standard_library_from(dir.as_deref())
+    }
+
+    /// Loads the standard library, taking each module from `dir` instead of
+    /// the binary when the directory has it.
+    pub fn load_standard_library_from(&mut self, dir: Option<&Path>) -> Result<()> {
+        for &(name, embedded) in stdlib::MODULES {
+            let source = stdlib::source(name, embedded, dir)?;
 
-        let stdlib_path = dir.to_str().unwrap();
+            self.lisp_engine.run_all(&source, env_root(&self.env))?;
+        }
 
-        self.load_file(stdlib_path)
+        Ok(())
     }
 
-    pub fn load_initrc(&mut self) -> Result<()> {
-        let mut path = match home_dir() {
-            Some(path) => path,
-            None => panic!("Could not determine HOME directory"),
+    /// Runs the lisp forms in the startup file at `path`, stopping at the
+    /// first error, which is reported with the line where its form starts.
+    pub fn load_startup_file(&mut self, path: &Path) -> Result<()> {
+        let location = |line, error| match error {
+            Error::Exit(code) => Error::Exit(code),
+            error => Error::LoadError(path.display().to_string(), line, Box::new(error)),
         };
 
-        path.push(".lishrc.lish");
+        let mut buffer = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut buffer)).map_err(|e| location(None, Error::from(e)))?;
+
+        let mut form = String::new();
+        let mut start = 1;
+
+        for (index, line) in buffer.lines().enumerate() {
+            if form.trim().is_empty() {
+                form.clear();
+                start = index + 1;
+            }
+
+            form.push_str(line);
+            form.push('\n');
 
-        if path.exists() {
-            let config_file_path = match path.to_str() {
-                Some(value) => value,
-                None => panic!("Could not determine HOME directory"),
-            };
+            if !form.trim().is_empty() && is_complete_lisp(&form) {
+                self.eval_str(&form).map_err(|e| location(Some(start), e))?;
+                form.clear();
+            }
+        }
 
-            self.load_file(config_file_path)
-        } else {
-            Ok(())
+        if !form.trim().is_empty() {
+            self.eval_str(&form).map_err(|e| location(Some(start), e))?;
         }
+
+        Ok(())
     }
 
     pub fn load_file(&mut self, file_name: &str) -> Result<()> {
@@ -84,8 +134,22 @@ impl Engine {
 
         File::open(file_name).and_then(|mut f| f.read_to_string(&mut buffer))?;
 
-        self.run(&format!("(do {})", buffer))?;
+        self.lisp_engine.run_all(&buffer, self.env.clone())?;
 
         Ok(())
     }
 }
+
+/// Runs a line of input, or a chunk of a script read by `source`: a lisp form
+/// when it starts with `(` and shell commands otherwise.
+pub fn run_line(input: &str, env: &Env, state: &mut State) -> Result<Option<LispValue>> {
+    match input.chars().next() {
+        None => Ok(None),
+        Some('(') => Ok(Some(LispEngine::new().run(input, env.clone())?)),
+        Some(_) => {
+            shell_engine::run_line(input, env.clone(), state)?;
+
+            Ok(None)
+        },
+    }
+}
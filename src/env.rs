@@ -24,7 +24,8 @@ pub fn env_set(env: &Env, key: &str, value: LispValue) {
     env.borrow_mut().data.insert(key.to_owned(), value);
 }
 
-fn env_find(env: &Env, name: &str) -> Option<Env> {
+/// The innermost environment where `name` is bound.
+pub fn env_find(env: &Env, name: &str) -> Option<Env> {
     let envdata = env.borrow();
     if envdata.data.contains_key(name) {
         Some(env.clone())
@@ -36,6 +37,14 @@ fn env_find(env: &Env, name: &str) -> Option<Env> {
     }
 }
 
+/// The outermost environment, where the core functions are defined.
+pub fn env_root(env: &Env) -> Env {
+    match env.borrow().outer {
+        Some(ref outer) => env_root(outer),
+        None => env.clone(),
+    }
+}
+
 pub fn env_get(env: &Env, name: &str) -> Result<LispValue> {
     match env_find(env, name) {
         Some(env) => {
@@ -72,6 +81,15 @@ mod tests {
         assert_eq!(env_get(&env, "b").unwrap(), types::integer(18));
     }
 
+    #[test]
+    fn test_root_env() {
+        let outer = env_new(None);
+        let env = env_new(Some(env_new(Some(outer.clone()))));
+
+        assert!(Rc::ptr_eq(&env_root(&env), &outer));
+        assert!(Rc::ptr_eq(&env_root(&outer), &outer));
+    }
+
     #[test]
     fn test_set_in_env_doesnt_change_outer() {
         let outer = env_new(None);
@@ -82,4 +100,15 @@ mod tests {
 
         assert_eq!(env_get(&outer, "b").unwrap(), types::integer(18));
     }
+
+    #[test]
+    fn test_find_binding_env() {
+        let outer = env_new(None);
+        env_set(&outer, "b", types::integer(18));
+
+        let env = env_new(Some(outer.clone()));
+
+        assert_eq!(env_find(&env, "b"), Some(outer));
+        assert_eq!(env_find(&env, "c"), None);
+    }
 }
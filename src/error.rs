@@ -1,11 +1,16 @@
 extern crate rustyline;
 
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
 use std::result;
 use std::io;
 
 use self::rustyline::error::ReadlineError;
 
-use types::LispValue;
+use json;
+use printer::Printer;
+use types::{self, Arity, HashKey, LispValue};
 
 pub type Result<T> = result::Result<T, Error>;
 
@@ -15,6 +20,9 @@ pub enum Error {
     Interrupted,
     Eof,
 
+    // startup files
+    LoadError(String, Option<usize>, Box<Error>),
+
     // lexer
     UnexpectedCharacter(char),
     UnknownLexerError,
@@ -28,12 +36,21 @@ pub enum Error {
 
     // shell
     CommandNotFound(String),
+    AliasNotFound(String),
+    BuiltinError(String, String),
+    Exit(i32),
 
     // lisp
     UndefinedBinding(String),
+    ArityError(String, Arity, usize),
+    BadSyntax(String, String),
+    IntegerOverflow,
     ApplyEmptyList,
     ApplyNonFunction(LispValue),
     TypeError,
+    IndexOutOfRange(i64),
+    RequireError(String, String),
+    DepthExceeded,
 }
 
 impl From<ReadlineError> for Error {
@@ -51,3 +68,155 @@ impl From<io::Error> for Error {
         Error::IoError(err)
     }
 }
+
+impl Error {
+    /// The part of lish the error comes from, which starts its message.
+    pub fn category(&self) -> &'static str {
+        match *self {
+            Error::Interrupted | Error::Eof => "input",
+            Error::LoadError(..) => "load",
+            Error::UnexpectedCharacter(_) | Error::UnknownLexerError => "lexer",
+            Error::ParseError => "parser",
+            Error::ReadlineError(_) => "readline",
+            Error::IoError(_) => "io",
+            Error::CommandNotFound(_) | Error::AliasNotFound(_) | Error::BuiltinError(..) | Error::Exit(_) => "shell",
+            Error::UndefinedBinding(_) => "lookup",
+            Error::ArityError(..) => "arity",
+            Error::BadSyntax(..) => "syntax",
+            Error::IntegerOverflow => "math",
+            Error::ApplyEmptyList | Error::ApplyNonFunction(_) => "apply",
+            Error::TypeError => "type",
+            Error::IndexOutOfRange(_) => "index",
+            Error::RequireError(..) => "require",
+            Error::DepthExceeded => "depth",
+        }
+    }
+
+    /// A short name for the kind of error, unique within its category.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Error::Interrupted => "interrupted",
+            Error::Eof => "eof",
+            Error::LoadError(..) => "load-error",
+            Error::UnexpectedCharacter(_) => "unexpected-character",
+            Error::UnknownLexerError => "unknown",
+            Error::ParseError => "parse-error",
+            Error::ReadlineError(_) => "readline-error",
+            Error::IoError(_) => "io-error",
+            Error::CommandNotFound(_) => "command-not-found",
+            Error::AliasNotFound(_) => "alias-not-found",
+            Error::BuiltinError(..) => "builtin-error",
+            Error::Exit(_) => "exit",
+            Error::UndefinedBinding(_) => "undefined-binding",
+            Error::ArityError(..) => "wrong-arity",
+            Error::BadSyntax(..) => "bad-syntax",
+            Error::IntegerOverflow => "integer-overflow",
+            Error::ApplyEmptyList => "empty-list",
+            Error::ApplyNonFunction(_) => "non-function",
+            Error::TypeError => "type-error",
+            Error::IndexOutOfRange(_) => "out-of-range",
+            Error::RequireError(..) => "require-error",
+            Error::DepthExceeded => "too-deep",
+        }
+    }
+
+    /// The error as a hash map with its `:category`, `:code` and `:message`,
+    /// and details like the `:name` of an undefined binding or the `:cause`
+    /// of an error in a startup file.
+    pub fn to_value(&self) -> LispValue {
+        let mut data = HashMap::new();
+
+        data.insert(HashKey::Keyword("category".to_owned()), types::string(self.category().to_owned()));
+        data.insert(HashKey::Keyword("code".to_owned()), types::string(self.code().to_owned()));
+        data.insert(HashKey::Keyword("message".to_owned()), types::string(self.to_string()));
+
+        let mut detail = |name: &str, value| {
+            data.insert(HashKey::Keyword(name.to_owned()), value);
+        };
+
+        match *self {
+            Error::LoadError(ref file, line, ref cause) => {
+                detail("file", types::string(file.to_owned()));
+                detail("line", line.map_or_else(types::nil, |line| types::integer(line as i64)));
+                detail("cause", cause.to_value());
+            },
+            Error::UnexpectedCharacter(c) => detail("character", types::character(c)),
+            Error::CommandNotFound(ref name) | Error::AliasNotFound(ref name) | Error::UndefinedBinding(ref name) |
+            Error::BuiltinError(ref name, _) | Error::RequireError(ref name, _) => detail("name", types::string(name.to_owned())),
+            Error::ArityError(ref name, _, count) => {
+                detail("name", types::string(name.to_owned()));
+                detail("count", types::integer(count as i64));
+            },
+            Error::BadSyntax(ref form, _) => detail("name", types::string(form.to_owned())),
+            Error::Exit(code) => detail("status", types::integer(i64::from(code))),
+            Error::ApplyNonFunction(ref value) => detail("value", value.clone()),
+            Error::IndexOutOfRange(index) => detail("index", types::integer(index)),
+            _ => {},
+        }
+
+        types::hash_map(data)
+    }
+
+    /// Exit status of a command that failed with the error: 127 when it
+    /// wasn't found, as in other shells, and 1 otherwise.
+    pub fn status(&self) -> i32 {
+        match *self {
+            Error::CommandNotFound(_) => 127,
+            Error::Exit(code) => code,
+            _ => 1,
+        }
+    }
+
+    /// Prints the error to standard error, as a JSON object if `json`.
+    pub fn report(&self, json: bool) {
+        if json {
+            match json::to_json(&self.to_value()) {
+                Ok(json) => eprintln!("{}", json),
+                Err(_) => eprintln!("{}", self),
+            }
+        } else {
+            eprintln!("{}", self);
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Interrupted => write!(f, "interrupted"),
+            Error::Eof => write!(f, "end of input"),
+            Error::LoadError(ref file, Some(line), ref error) => write!(f, "{}:{}: {}", file, line, error),
+            Error::LoadError(ref file, None, ref error) => write!(f, "{}: {}", file, error),
+            Error::UnexpectedCharacter(c) => write!(f, "lexer error: unexpected character `{}`", c),
+            Error::UnknownLexerError => write!(f, "lexer error: unknown"),
+            Error::ParseError => write!(f, "parser error"),
+            Error::ReadlineError(ref e) => write!(f, "readline error: {}", e),
+            Error::IoError(ref e) => write!(f, "io error: {}", e),
+            Error::CommandNotFound(ref command) => write!(f, "shell error: command not found: {}", command),
+            Error::AliasNotFound(ref name) => write!(f, "shell error: alias not found: {}", name),
+            Error::BuiltinError(ref name, ref message) => write!(f, "shell error: {}: {}", name, message),
+            Error::Exit(code) => write!(f, "exit: {}", code),
+            Error::UndefinedBinding(ref name) => write!(f, "lookup error: undefined binding: {}", name),
+            Error::ArityError(ref name, arity, count) => write!(f, "arity error: {}: expected {}, got {}", name, arity, count),
+            Error::BadSyntax(ref form, ref message) => write!(f, "syntax error: {}: {}", form, message),
+            Error::IntegerOverflow => write!(f, "integer overflow"),
+            Error::ApplyEmptyList => write!(f, "apply error: unable to apply empty list"),
+            Error::ApplyNonFunction(ref value) => write!(f, "apply error: expected function, got: {}", Printer::new().print(value, true)),
+            Error::TypeError => write!(f, "type error"),
+            Error::IndexOutOfRange(index) => write!(f, "index error: index out of range: {}", index),
+            Error::RequireError(ref name, ref message) => write!(f, "require error: {}: {}", name, message),
+            Error::DepthExceeded => write!(f, "depth error: nested too deeply"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::ReadlineError(ref e) => Some(e),
+            Error::IoError(ref e) => Some(e),
+            Error::LoadError(_, _, ref e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use error::Result;
+use lock::FileLock;
+use paths;
+
+/// Once the ranks add up to more than this, every rank is aged.
+const MAX_TOTAL_RANK: f64 = 9000.0;
+
+/// Factor applied to every rank when aging.
+const AGING_FACTOR: f64 = 0.99;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Dir {
+    pub path: String,
+    pub rank: f64,
+    pub last_access: u64,
+}
+
+impl Dir {
+    /// Rank weighted by how recently the directory was visited.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_access);
+
+        let weight = if age < 3600 {
+            4.0
+        } else if age < 86400 {
+            2.0
+        } else if age < 604800 {
+            0.5
+        } else {
+            0.25
+        };
+
+        self.rank * weight
+    }
+
+    /// Whether every keyword appears in the path, in order, ignoring case.
+    pub fn matches(&self, keywords: &[String]) -> bool {
+        let path = self.path.to_lowercase();
+        let mut rest = path.as_str();
+
+        for keyword in keywords {
+            let keyword = keyword.to_lowercase();
+
+            match rest.find(&keyword) {
+                Some(index) => rest = &rest[index + keyword.len()..],
+                None => return false,
+            }
+        }
+
+        true
+    }
+
+    fn parse(line: &str) -> Option<Dir> {
+        let mut fields = line.splitn(3, '\t');
+
+        let rank = fields.next()?.parse().ok()?;
+        let last_access = fields.next()?.parse().ok()?;
+        let path = fields.next()?.to_owned();
+
+        Some(Dir {
+            path: path,
+            rank: rank,
+            last_access: last_access,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        format!("{}\t{}\t{}\n", self.rank, self.last_access, self.path)
+    }
+}
+
+pub fn now() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+/// Directories visited with `cd`, ranked by frequency and recency.
+///
+/// Like the history file, the database is shared by every running session
+/// and only rewritten while holding an exclusive `flock`.
+pub struct Database {
+    path: Option<PathBuf>,
+}
+
+impl Database {
+    pub fn new() -> Database {
+        Database { path: default_path() }
+    }
+
+    pub fn load(&self) -> Result<Vec<Dir>> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(vec![]),
+        };
+
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut file = File::open(path)?;
+        let _lock = FileLock::shared(&file)?;
+
+        let mut buffer = String::new();
+        file.read_to_string(&mut buffer)?;
+
+        Ok(buffer.lines().filter_map(Dir::parse).collect())
+    }
+
+    /// Records a visit to `dir` at time `now`.
+    pub fn visit(&self, dir: &Path, now: u64) -> Result<()> {
+        let path = dir.to_string_lossy();
+        if path.contains('\n') {
+            return Ok(());
+        }
+
+        let mut file = match self.open_for_writing()? {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        let _lock = FileLock::exclusive(&file)?;
+
+        let mut buffer = String::new();
+        file.read_to_string(&mut buffer)?;
+
+        let mut dirs: Vec<Dir> = buffer.lines().filter_map(Dir::parse).collect();
+        add_visit(&mut dirs, &path, now);
+
+        let data: String = dirs.iter().map(Dir::serialize).collect();
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(data.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Existing directories matching `keywords`, best match first.
+    pub fn rank(&self, keywords: &[String], now: u64) -> Result<Vec<Dir>> {
+        let dirs = self.load()?.into_iter()
+            .filter(|dir| Path::new(&dir.path).is_dir())
+            .collect();
+
+        Ok(rank(dirs, keywords, now))
+    }
+
+    fn open_for_writing(&self) -> Result<Option<File>> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(None),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new().read(true).write(true).create(true).open(path)?;
+
+        Ok(Some(file))
+    }
+}
+
+/// `$XDG_DATA_HOME/lish/dirs` when XDG_DATA_HOME is set, `~/.lish_dirs` otherwise.
+pub fn default_path() -> Option<PathBuf> {
+    paths::data_file("dirs", ".lish_dirs")
+}
+
+/// Bumps the rank of `path`, aging every entry once the total grows too large
+/// and forgetting the ones that drop below 1.
+fn add_visit(dirs: &mut Vec<Dir>, path: &str, now: u64) {
+    match dirs.iter_mut().find(|dir| dir.path == path) {
+        Some(dir) => {
+            dir.rank += 1.0;
+            dir.last_access = now;
+        },
+        None => {
+            dirs.push(Dir {
+                path: path.to_owned(),
+                rank: 1.0,
+                last_access: now,
+            });
+        },
+    }
+
+    let total: f64 = dirs.iter().map(|dir| dir.rank).sum();
+
+    if total > MAX_TOTAL_RANK {
+        for dir in dirs.iter_mut() {
+            dir.rank *= AGING_FACTOR;
+        }
+        dirs.retain(|dir| dir.rank >= 1.0);
+    }
+}
+
+/// Keeps the directories matching `keywords`, sorted by descending frecency.
+fn rank(dirs: Vec<Dir>, keywords: &[String], now: u64) -> Vec<Dir> {
+    let mut dirs: Vec<Dir> = dirs.into_iter().filter(|dir| dir.matches(keywords)).collect();
+
+    dirs.sort_by(|a, b| {
+        b.frecency(now).partial_cmp(&a.frecency(now)).unwrap_or(::std::cmp::Ordering::Equal)
+    });
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+    use super::*;
+
+    fn dir(path: &str, rank: f64, last_access: u64) -> Dir {
+        Dir {
+            path: path.to_owned(),
+            rank: rank,
+            last_access: last_access,
+        }
+    }
+
+    fn keywords(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn serialize_and_parse_dir() {
+        let dir = dir("/home/user/a b", 2.5, 42);
+
+        assert_eq!(Dir::parse(dir.serialize().trim_end_matches('\n')), Some(dir));
+    }
+
+    #[test]
+    fn match_keywords_in_order() {
+        let dir = dir("/home/user/Projects/lish", 1.0, 0);
+
+        assert!(dir.matches(&keywords(&["proj", "lish"])));
+        assert!(!dir.matches(&keywords(&["lish", "proj"])));
+        assert!(!dir.matches(&keywords(&["rust"])));
+    }
+
+    #[test]
+    fn rank_prefers_recent_visits() {
+        let now = 1000000;
+        let dirs = vec![
+            dir("/old/project", 10.0, now - 700000),
+            dir("/new/project", 2.0, now - 60),
+        ];
+
+        let ranked = rank(dirs, &keywords(&["project"]), now);
+
+        assert_eq!(ranked[0].path, "/new/project");
+        assert_eq!(ranked[1].path, "/old/project");
+    }
+
+    #[test]
+    fn rank_prefers_frequent_visits() {
+        let now = 1000000;
+        let dirs = vec![
+            dir("/a/project", 1.0, now - 60),
+            dir("/b/project", 5.0, now - 60),
+        ];
+
+        assert_eq!(rank(dirs, &keywords(&["project"]), now)[0].path, "/b/project");
+    }
+
+    #[test]
+    fn visits_age_old_entries() {
+        let mut dirs = vec![dir("/big", MAX_TOTAL_RANK, 0), dir("/small", 1.0, 0)];
+
+        add_visit(&mut dirs, "/big", 10);
+
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].path, "/big");
+        assert_eq!(dirs[0].last_access, 10);
+    }
+
+    #[test]
+    fn visit_and_load() {
+        let mut path = env::temp_dir();
+        path.push(format!("lish-dirs-{}", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let database = Database { path: Some(path) };
+
+        database.visit(Path::new("/tmp"), 1).unwrap();
+        database.visit(Path::new("/usr"), 2).unwrap();
+        database.visit(Path::new("/tmp"), 3).unwrap();
+
+        assert_eq!(database.load().unwrap(), vec![dir("/tmp", 2.0, 3), dir("/usr", 1.0, 2)]);
+    }
+}
@@ -0,0 +1,335 @@
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use error::{Error, Result};
+use env::{Env, env_get};
+use lock::FileLock;
+use paths;
+use types::LispType;
+
+pub const DEFAULT_HISTORY_SIZE: usize = 10000;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Entry {
+    pub timestamp: u64,
+    pub cwd: String,
+    pub command: String,
+}
+
+impl Entry {
+    pub fn new(command: &str) -> Entry {
+        let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => 0,
+        };
+
+        let cwd = match env::current_dir() {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(_) => String::new(),
+        };
+
+        Entry {
+            timestamp: timestamp,
+            cwd: cwd,
+            command: command.to_owned(),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let mut fields = line.splitn(3, '\t');
+
+        let timestamp = fields.next()?.parse().ok()?;
+        let cwd = unescape(fields.next()?);
+        let command = unescape(fields.next()?);
+
+        Some(Entry {
+            timestamp: timestamp,
+            cwd: cwd,
+            command: command,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        format!("{}\t{}\t{}\n", self.timestamp, escape(&self.cwd), escape(&self.command))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Append-only history file shared by every running lish session.
+///
+/// Each session appends its own entries while holding an exclusive `flock`,
+/// so concurrent sessions interleave whole lines and never overwrite each
+/// other's history, and picks up the entries of the others with `load_new`.
+pub struct History {
+    path: Option<PathBuf>,
+    /// Length of the file already read, after which come the entries other
+    /// sessions appended since.
+    read: Cell<u64>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::with_path(default_path())
+    }
+
+    fn with_path(path: Option<PathBuf>) -> History {
+        History {
+            path: path,
+            read: Cell::new(0),
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<Entry>> {
+        self.read.set(0);
+        self.load_new()
+    }
+
+    /// Entries appended since the file was last read, by other sessions.
+    pub fn load_new(&self) -> Result<Vec<Entry>> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(vec![]),
+        };
+
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut file = File::open(path)?;
+        let _lock = FileLock::shared(&file)?;
+
+        self.read_new(&mut file)
+    }
+
+    /// Appends `entry` and returns the entries that other sessions appended
+    /// before it since the file was last read.
+    pub fn append(&self, entry: &Entry) -> Result<Vec<Entry>> {
+        let mut file = match self.open_for_writing()? {
+            Some(file) => file,
+            None => return Ok(vec![]),
+        };
+        let _lock = FileLock::exclusive(&file)?;
+
+        let entries = self.read_new(&mut file)?;
+
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(entry.serialize().as_bytes())?;
+        self.read.set(file.seek(SeekFrom::Current(0))?);
+
+        Ok(entries)
+    }
+
+    fn read_new(&self, file: &mut File) -> Result<Vec<Entry>> {
+        // another session truncated the file, which only drops entries
+        // that were already read
+        let len = file.metadata()?.len();
+        if len < self.read.get() {
+            self.read.set(len);
+        }
+
+        file.seek(SeekFrom::Start(self.read.get()))?;
+
+        let mut buffer = String::new();
+        file.read_to_string(&mut buffer)?;
+        self.read.set(self.read.get() + buffer.len() as u64);
+
+        Ok(buffer.lines().filter_map(Entry::parse).collect())
+    }
+
+    /// Drops the oldest entries so that at most `max_size` remain.
+    pub fn truncate(&self, max_size: usize) -> Result<()> {
+        let mut file = match self.open_for_writing()? {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        let _lock = FileLock::exclusive(&file)?;
+
+        let mut buffer = String::new();
+        file.read_to_string(&mut buffer)?;
+
+        let entries: Vec<Entry> = buffer.lines().filter_map(Entry::parse).collect();
+        if entries.len() <= max_size {
+            return Ok(());
+        }
+
+        let kept: String = entries[entries.len() - max_size..].iter().map(Entry::serialize).collect();
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(kept.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn open_for_writing(&self) -> Result<Option<File>> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(None),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new().read(true).write(true).create(true).open(path)?;
+
+        Ok(Some(file))
+    }
+}
+
+/// `$XDG_DATA_HOME/lish/history` when XDG_DATA_HOME is set, `~/.lish_history` otherwise.
+pub fn default_path() -> Option<PathBuf> {
+    paths::data_file("history", ".lish_history")
+}
+
+/// Maximum number of entries to keep, from the `HISTORY_SIZE` binding.
+pub fn max_size(env: &Env) -> Result<usize> {
+    match env_get(env, "HISTORY_SIZE").ok() {
+        Some(value) => {
+            match *value {
+                LispType::Integer(size) if size >= 0 => Ok(size as usize),
+                _ => Err(Error::TypeError),
+            }
+        },
+        None => Ok(DEFAULT_HISTORY_SIZE),
+    }
+}
+
+/// Whether `line` matches one of the regular expressions in `HISTORY_IGNORE`.
+pub fn is_ignored(line: &str, env: &Env) -> Result<bool> {
+    if line.trim().is_empty() {
+        return Ok(true);
+    }
+
+    let value = match env_get(env, "HISTORY_IGNORE").ok() {
+        Some(value) => value,
+        None => return Ok(false),
+    };
+
+    match *value {
+        LispType::Nil => Ok(false),
+        LispType::List(ref patterns) => {
+            for pattern in patterns {
+                match **pattern {
+                    LispType::Strn(ref pattern) => {
+                        let regex = ::regex::Regex::new(pattern).map_err(|_| Error::TypeError)?;
+                        if regex.is_match(line) {
+                            return Ok(true);
+                        }
+                    },
+                    _ => return Err(Error::TypeError),
+                }
+            }
+
+            Ok(false)
+        },
+        _ => Err(Error::TypeError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+    use types;
+    use env::{env_new, env_set};
+    use super::*;
+
+    fn temp_history(name: &str) -> History {
+        let mut path = env::temp_dir();
+        path.push(format!("lish-history-{}-{}", name, process::id()));
+        let _ = fs::remove_file(&path);
+
+        History::with_path(Some(path))
+    }
+
+    fn entry(command: &str) -> Entry {
+        Entry {
+            timestamp: 42,
+            cwd: "/tmp".to_owned(),
+            command: command.to_owned(),
+        }
+    }
+
+    #[test]
+    fn serialize_and_parse_entry() {
+        let entry = entry("echo \"a\tb\"\n(+ 1 2) \\n");
+
+        assert_eq!(Entry::parse(entry.serialize().trim_end_matches('\n')), Some(entry));
+    }
+
+    #[test]
+    fn append_and_load() {
+        let history = temp_history("append");
+
+        history.append(&entry("ls")).unwrap();
+        history.append(&entry("cd dir1")).unwrap();
+
+        assert_eq!(history.load().unwrap(), vec![entry("ls"), entry("cd dir1")]);
+    }
+
+    #[test]
+    fn load_new_entries_of_other_sessions() {
+        let first = temp_history("sessions");
+        let second = History::with_path(first.path.clone());
+
+        assert_eq!(first.load().unwrap(), vec![]);
+        assert_eq!(second.append(&entry("ls")).unwrap(), vec![]);
+        assert_eq!(first.load_new().unwrap(), vec![entry("ls")]);
+        assert_eq!(first.load_new().unwrap(), vec![]);
+
+        assert_eq!(first.append(&entry("pwd")).unwrap(), vec![]);
+        assert_eq!(second.append(&entry("cd dir1")).unwrap(), vec![entry("pwd")]);
+        assert_eq!(first.append(&entry("cd ..")).unwrap(), vec![entry("cd dir1")]);
+        assert_eq!(first.load_new().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn truncate_keeps_newest_entries() {
+        let history = temp_history("truncate");
+
+        history.append(&entry("a")).unwrap();
+        history.append(&entry("b")).unwrap();
+        history.append(&entry("c")).unwrap();
+        history.truncate(2).unwrap();
+
+        assert_eq!(history.load().unwrap(), vec![entry("b"), entry("c")]);
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let env = env_new(None);
+        env_set(&env, "HISTORY_IGNORE", types::list(vec![types::string("^ls$".to_owned())]));
+
+        assert!(is_ignored("ls", &env).unwrap());
+        assert!(is_ignored("  ", &env).unwrap());
+        assert!(!is_ignored("ls -la", &env).unwrap());
+    }
+}
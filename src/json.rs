@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use error::{Error, Result};
+use types::{self, AtomType, HashKey, LispType, LispValue};
+
+/// Writes `value` as JSON on a single line.
+///
+/// Lists and vectors become arrays and hash maps objects, with keys written
+/// as strings and sorted. Keywords and characters are written as strings, and
+/// values with no JSON form, like functions, atoms containing themselves or
+/// maps with keys that become the same string, like `"a"` and `:a`, fail
+/// with a type error.
+pub fn to_json(value: &LispValue) -> Result<String> {
+    let mut output = String::new();
+
+    write_value(&mut output, value, &mut vec![])?;
+
+    Ok(output)
+}
+
+/// `atoms` are the atoms whose value is being written.
+fn write_value(output: &mut String, value: &LispValue, atoms: &mut Vec<*const AtomType>) -> Result<()> {
+    match **value {
+        LispType::Nil => output.push_str("null"),
+        LispType::Bool(b) => output.push_str(if b { "true" } else { "false" }),
+        LispType::Integer(i) => output.push_str(&i.to_string()),
+        LispType::Strn(ref s) | LispType::Symbol(ref s) | LispType::Keyword(ref s) => write_string(output, s),
+        LispType::Char(c) => write_string(output, &c.to_string()),
+        LispType::List(ref values) | LispType::Vector(ref values) => {
+            output.push('[');
+
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_value(output, value, atoms)?;
+            }
+
+            output.push(']');
+        },
+        LispType::HashMap(ref data) => {
+            let mut names = HashSet::new();
+
+            output.push('{');
+
+            for (i, (key, value)) in types::sorted_entries(data).into_iter().enumerate() {
+                let name = key_name(key);
+
+                if !names.insert(name.clone()) {
+                    return Err(Error::TypeError);
+                }
+
+                if i > 0 {
+                    output.push(',');
+                }
+                write_string(output, &name);
+                output.push(':');
+                write_value(output, value, atoms)?;
+            }
+
+            output.push('}');
+        },
+        LispType::Atom(ref atom) => {
+            let id = atom as *const AtomType;
+
+            if atoms.contains(&id) {
+                return Err(Error::TypeError);
+            }
+
+            atoms.push(id);
+            write_value(output, &atom.value.borrow(), atoms)?;
+            atoms.pop();
+        },
+        LispType::Function(_) | LispType::NativeFunction(_) => return Err(Error::TypeError),
+    }
+
+    Ok(())
+}
+
+fn key_name(key: &HashKey) -> String {
+    match *key {
+        HashKey::Integer(i) => i.to_string(),
+        HashKey::Char(c) => c.to_string(),
+        HashKey::Keyword(ref name) => name.to_owned(),
+        HashKey::Strn(ref s) => s.to_owned(),
+    }
+}
+
+fn write_string(output: &mut String, s: &str) {
+    output.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+}
+
+/// Arrays and objects nested deeper than this fail to read.
+const MAX_DEPTH: usize = 1000;
+
+/// Reads the JSON document in `input`.
+///
+/// Arrays become lists and objects hash maps with keyword keys. Lish only has
+/// integers, so other numbers fail with a parse error.
+pub fn from_json(input: &str) -> Result<LispValue> {
+    let mut chars = input.chars().peekable();
+
+    let value = read_value(&mut chars, 0)?;
+
+    skip_whitespace(&mut chars);
+
+    match chars.next() {
+        None => Ok(value),
+        Some(_) => Err(Error::ParseError),
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: &str) -> Result<()> {
+    for c in expected.chars() {
+        if chars.next() != Some(c) {
+            return Err(Error::ParseError);
+        }
+    }
+
+    Ok(())
+}
+
+fn read_value(chars: &mut Peekable<Chars>, depth: usize) -> Result<LispValue> {
+    skip_whitespace(chars);
+
+    match chars.peek().cloned() {
+        Some('n') => expect(chars, "null").map(|_| types::nil()),
+        Some('t') => expect(chars, "true").map(|_| types::boolean(true)),
+        Some('f') => expect(chars, "false").map(|_| types::boolean(false)),
+        Some('"') => read_string(chars).map(types::string),
+        Some('[') | Some('{') if depth >= MAX_DEPTH => Err(Error::DepthExceeded),
+        Some('[') => read_array(chars, depth + 1),
+        Some('{') => read_object(chars, depth + 1),
+        Some(c) if c == '-' || c.is_ascii_digit() => read_integer(chars),
+        _ => Err(Error::ParseError),
+    }
+}
+
+fn read_integer(chars: &mut Peekable<Chars>) -> Result<LispValue> {
+    let mut text = String::new();
+
+    while let Some(&c) = chars.peek() {
+        if c == '-' || c.is_ascii_digit() {
+            text.push(c);
+            chars.next();
+        } else if c == '.' || c == 'e' || c == 'E' {
+            return Err(Error::ParseError);
+        } else {
+            break;
+        }
+    }
+
+    text.parse().map(types::integer).map_err(|_| Error::ParseError)
+}
+
+fn read_string(chars: &mut Peekable<Chars>) -> Result<String> {
+    expect(chars, "\"")?;
+
+    let mut s = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(s),
+            Some('\\') => match chars.next() {
+                Some('n') => s.push('\n'),
+                Some('r') => s.push('\r'),
+                Some('t') => s.push('\t'),
+                Some('b') => s.push('\u{8}'),
+                Some('f') => s.push('\u{c}'),
+                Some('u') => s.push(read_escape(chars)?),
+                Some(c) => s.push(c),
+                None => return Err(Error::ParseError),
+            },
+            Some(c) => s.push(c),
+            None => return Err(Error::ParseError),
+        }
+    }
+}
+
+/// The character of a `\uXXXX` escape, which may be followed by a second one
+/// for characters outside the basic plane.
+fn read_escape(chars: &mut Peekable<Chars>) -> Result<char> {
+    let first = read_hex(chars)?;
+
+    let code = if (0xd800..0xdc00).contains(&first) {
+        expect(chars, "\\u")?;
+        let second = read_hex(chars)?;
+
+        0x10000 + ((first - 0xd800) << 10) + (second.wrapping_sub(0xdc00) & 0x3ff)
+    } else {
+        first
+    };
+
+    ::std::char::from_u32(code).ok_or(Error::ParseError)
+}
+
+fn read_hex(chars: &mut Peekable<Chars>) -> Result<u32> {
+    let digits: String = chars.take(4).collect();
+
+    if digits.len() != 4 {
+        return Err(Error::ParseError);
+    }
+
+    u32::from_str_radix(&digits, 16).map_err(|_| Error::ParseError)
+}
+
+fn read_array(chars: &mut Peekable<Chars>, depth: usize) -> Result<LispValue> {
+    expect(chars, "[")?;
+    skip_whitespace(chars);
+
+    let mut values = vec![];
+
+    if chars.peek() == Some(&']') {
+        chars.next();
+        return Ok(types::list(values));
+    }
+
+    loop {
+        values.push(read_value(chars, depth)?);
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => {},
+            Some(']') => return Ok(types::list(values)),
+            _ => return Err(Error::ParseError),
+        }
+    }
+}
+
+fn read_object(chars: &mut Peekable<Chars>, depth: usize) -> Result<LispValue> {
+    expect(chars, "{")?;
+    skip_whitespace(chars);
+
+    let mut data = HashMap::new();
+
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Ok(types::hash_map(data));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        let key = read_string(chars)?;
+
+        skip_whitespace(chars);
+        expect(chars, ":")?;
+
+        data.insert(HashKey::Keyword(key), read_value(chars, depth)?);
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => {},
+            Some('}') => return Ok(types::hash_map(data)),
+            _ => return Err(Error::ParseError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use types::{self, HashKey};
+    use super::*;
+
+    #[test]
+    fn write_scalars() {
+        assert_eq!(to_json(&types::nil()).unwrap(), "null");
+        assert_eq!(to_json(&types::boolean(true)).unwrap(), "true");
+        assert_eq!(to_json(&types::integer(-3)).unwrap(), "-3");
+        assert_eq!(to_json(&types::keyword("a".to_owned())).unwrap(), "\"a\"");
+        assert_eq!(to_json(&types::string("a \"b\"\n\u{1}".to_owned())).unwrap(), "\"a \\\"b\\\"\\n\\u0001\"");
+    }
+
+    #[test]
+    fn write_collections() {
+        let mut data = HashMap::new();
+        data.insert(HashKey::from("b"), types::list(vec![types::integer(1), types::nil()]));
+        data.insert(HashKey::Keyword("a".to_owned()), types::vector(vec![]));
+
+        assert_eq!(to_json(&types::hash_map(data)).unwrap(), "{\"a\":[],\"b\":[1,null]}");
+    }
+
+    #[test]
+    fn write_colliding_keys() {
+        let mut data = HashMap::new();
+        data.insert(HashKey::from("a"), types::integer(1));
+        data.insert(HashKey::Keyword("a".to_owned()), types::integer(2));
+
+        assert!(to_json(&types::hash_map(data)).is_err());
+    }
+
+    #[test]
+    fn write_function() {
+        assert!(to_json(&types::native_function(|_, _| Ok(types::nil()))).is_err());
+    }
+
+    #[test]
+    fn read_scalars() {
+        assert_eq!(from_json(" null ").unwrap(), types::nil());
+        assert_eq!(from_json("false").unwrap(), types::boolean(false));
+        assert_eq!(from_json("-12").unwrap(), types::integer(-12));
+        assert_eq!(from_json("\"a\\n\\\"\\u00e9\\ud83d\\ude00\"").unwrap(), types::string("a\n\"\u{e9}\u{1f600}".to_owned()));
+    }
+
+    #[test]
+    fn read_collections() {
+        let mut data = HashMap::new();
+        data.insert(HashKey::Keyword("a".to_owned()), types::list(vec![types::integer(1), types::list(vec![])]));
+        data.insert(HashKey::Keyword("b".to_owned()), types::hash_map(HashMap::new()));
+
+        assert_eq!(from_json("{ \"a\": [1, []], \"b\": {} }").unwrap(), types::hash_map(data));
+    }
+
+    #[test]
+    fn read_invalid_json() {
+        for input in &["", "[1,", "{\"a\" 1}", "1.5", "nul", "\"a", "[1] 2", "\"\\u12\""] {
+            assert!(from_json(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn read_deep_nesting() {
+        assert!(from_json(&format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH))).is_ok());
+
+        for input in &["[".repeat(100_000), "{\"a\":".repeat(100_000)] {
+            match from_json(input) {
+                Err(Error::DepthExceeded) => {},
+                result => panic!("expected a depth error, got {:?}", result),
+            }
+        }
+    }
+}
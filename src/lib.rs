@@ -9,12 +9,23 @@ extern crate maplit;
 
 mod error;
 mod readliner;
+mod history;
+mod frecency;
+mod lock;
+mod paths;
 mod types;
 mod env;
 mod lisp;
 mod shell;
 mod core;
+mod convert;
+mod stdlib;
+mod json;
 
 pub mod printer;
 pub mod engine;
 pub mod repl;
+
+pub use error::{Error, Result};
+pub use types::{CallContext, HashKey, LispType, LispValue};
+pub use convert::{FromLisp, IntoLisp};
@@ -1,5 +1,5 @@
 use error::Result;
-use types::LispValue;
+use types::{self, LispValue};
 use env::Env;
 
 use lisp::parser::Parser;
@@ -27,4 +27,19 @@ impl Engine {
 
         self.evaluator.eval(expr, env)
     }
+
+    /// Evaluates every form in `input` in order, returning the value of the
+    /// last one, or nil when there are none.
+    pub fn run_all(&mut self, input: &str, env: Env) -> Result<LispValue> {
+        let mut parser = Parser::new();
+        parser.add_tokens(tokenize(input)?);
+
+        let mut result = types::nil();
+
+        for form in parser.parse_all()? {
+            result = self.evaluator.eval(form, env.clone())?;
+        }
+
+        Ok(result)
+    }
 }
@@ -1,9 +1,17 @@
-use types::{self, LispType, LispValue};
-use env::{Env, env_new, env_get, env_set};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use types::{self, Arity, CallContext, HashKey, LispType, LispValue};
+use env::{Env, env_new, env_get, env_set, env_find};
 use error::{Error, Result};
 
 enum FormType {
     Def,
+    Set,
+    Export,
+    Try,
+    ThreadFirst,
+    ThreadLast,
     Do,
     Fn,
     Function,
@@ -14,6 +22,11 @@ impl FormType {
     pub fn from(name: &str) -> FormType {
         match name {
             "def" => FormType::Def,
+            "set!" => FormType::Set,
+            "export" => FormType::Export,
+            "try" => FormType::Try,
+            "->" => FormType::ThreadFirst,
+            "->>" => FormType::ThreadLast,
             "do" => FormType::Do,
             "fn" => FormType::Fn,
             "eval" => FormType::Eval,
@@ -22,20 +35,72 @@ impl FormType {
     }
 }
 
-pub struct Evaluator;
+pub fn is_special_form(name: &str) -> bool {
+    match FormType::from(name) {
+        FormType::Function => false,
+        _ => true,
+    }
+}
+
+/// Value bound to `name`, where a qualified name like `git/branch` that isn't
+/// bound itself is looked up in the namespace `git`.
+pub fn lookup(env: &Env, name: &str) -> Result<LispValue> {
+    let error = match env_get(env, name) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+
+    let (namespace, member) = match name.find('/') {
+        Some(index) if index > 0 && index < name.len() - 1 => (&name[..index], &name[index + 1..]),
+        _ => return Err(error),
+    };
+
+    match env_get(env, namespace).ok() {
+        Some(value) => match *value {
+            LispType::HashMap(ref data) => data.get(&HashKey::from(member)).cloned().ok_or(error),
+            _ => Err(error),
+        },
+        None => Err(error),
+    }
+}
+
+fn bad_syntax(form: &str, message: &str) -> Error {
+    Error::BadSyntax(form.to_owned(), message.to_owned())
+}
+
+/// Most evaluations that may be in progress at once, through nested forms or
+/// function calls, so that deep recursion is an error instead of overflowing
+/// the stack.
+pub const MAX_DEPTH: usize = 1000;
+
+pub struct Evaluator {
+    depth: Cell<usize>,
+}
 
 impl Evaluator {
     pub fn new() -> Evaluator {
-        Evaluator
+        Evaluator {
+            depth: Cell::new(0),
+        }
     }
 
     pub fn eval(&self, lisp_expr: LispValue, env: Env) -> Result<LispValue> {
-        match *lisp_expr {
+        if self.depth.get() >= MAX_DEPTH {
+            return Err(Error::DepthExceeded);
+        }
+
+        self.depth.set(self.depth.get() + 1);
+
+        let result = match *lisp_expr {
             LispType::List(ref exprs) => {
                 self.apply(exprs.as_slice(), env)
             }
-            _ => Ok(self.eval_ast(lisp_expr.clone(), env)?),
-        }
+            _ => self.eval_ast(lisp_expr.clone(), env),
+        };
+
+        self.depth.set(self.depth.get() - 1);
+
+        result
     }
 
     fn apply(&self, list: &[LispValue], env: Env) -> Result<LispValue> {
@@ -55,6 +120,11 @@ impl Evaluator {
 
                 match form_type {
                     FormType::Def => self.eval_def(tail, env),
+                    FormType::Set => self.eval_set(tail, env),
+                    FormType::Export => self.eval_export(tail, env),
+                    FormType::Try => self.eval_try(tail, env),
+                    FormType::ThreadFirst => self.eval_thread("->", tail, env),
+                    FormType::ThreadLast => self.eval_thread("->>", tail, env),
                     FormType::Do => self.eval_do(tail, env),
                     FormType::Fn => self.eval_fn(tail, env),
                     FormType::Function => self.eval_function(list, env),
@@ -65,17 +135,139 @@ impl Evaluator {
     }
 
     fn eval_def(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
-        let a1 = args[0].clone();
-        let a2 = args[1].clone();
-        match *a1 {
-            LispType::Symbol(ref name) => {
-                let value = self.eval(a2, env.clone())?;
-                env_set(&env, name, value);
+        match args {
+            &[ref a1, ref a2] => match **a1 {
+                LispType::Symbol(ref name) => {
+                    let value = self.eval(a2.clone(), env.clone())?;
+
+                    // functions are named after the first binding, for errors
+                    let value = match *value {
+                        LispType::Function(ref data) if data.name.is_none() => data.named(name),
+                        _ => value.clone(),
+                    };
+
+                    env_set(&env, name, value);
+
+                    Ok(types::nil())
+                },
+                _ => Err(bad_syntax("def", "expected a symbol")),
+            },
+            _ => Err(bad_syntax("def", "expected a symbol and a value")),
+        }
+    }
+
+    /// `(set! name value)` changes the binding of `name` in the innermost
+    /// environment that has one, unlike `def` which always binds locally.
+    fn eval_set(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
+        let (name, value) = match args {
+            &[ref name, ref value] => match **name {
+                LispType::Symbol(ref name) => (name, value),
+                _ => return Err(bad_syntax("set!", "expected a symbol")),
+            },
+            _ => return Err(bad_syntax("set!", "expected a symbol and a value")),
+        };
+
+        let value = self.eval(value.clone(), env.clone())?;
+
+        match env_find(&env, name) {
+            Some(binding_env) => {
+                env_set(&binding_env, name, value);
 
                 Ok(types::nil())
             },
-            _ => panic!("def! of non-symbol"),
+            None => Err(Error::UndefinedBinding(name.to_owned())),
+        }
+    }
+
+    /// `(export name...)` adds names to the `*exports*` of a module, the only
+    /// bindings it shares with the code that requires it.
+    fn eval_export(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
+        let module_env = match env_find(&env, "*exports*") {
+            Some(module_env) => module_env,
+            None => return Err(bad_syntax("export", "only allowed in a module")),
+        };
+
+        let mut exports = match *env_get(&module_env, "*exports*")? {
+            LispType::List(ref names) => names.clone(),
+            _ => return Err(Error::TypeError),
+        };
+
+        for arg in args {
+            match **arg {
+                LispType::Symbol(ref name) => exports.push(types::string(name.to_owned())),
+                _ => return Err(bad_syntax("export", "expected symbols")),
+            }
+        }
+
+        env_set(&module_env, "*exports*", types::list(exports));
+
+        Ok(types::nil())
+    }
+
+    /// `(try expr (catch e handler...))` evaluates `expr` and, if it fails,
+    /// the handler with `e` bound to the error as a hash map with its
+    /// `:category`, `:code` and `:message`.
+    fn eval_try(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
+        let (expr, clause) = match args {
+            &[ref expr, ref clause] => match **clause {
+                LispType::List(ref clause) => (expr, clause),
+                _ => return Err(bad_syntax("try", "expected a catch clause")),
+            },
+            _ => return Err(bad_syntax("try", "expected an expression and a catch clause")),
+        };
+
+        let (name, handler) = match clause.as_slice() {
+            &[ref head, ref name, ref handler @ ..] if **head == LispType::Symbol("catch".to_owned()) => match **name {
+                LispType::Symbol(ref name) => (name, handler),
+                _ => return Err(bad_syntax("catch", "expected a symbol")),
+            },
+            _ => return Err(bad_syntax("try", "expected a catch clause")),
+        };
+
+        match self.eval(expr.clone(), env.clone()) {
+            Err(Error::Exit(code)) => Err(Error::Exit(code)),
+            Err(error) => {
+                let catch_env = env_new(Some(env));
+                env_set(&catch_env, name, error.to_value());
+
+                self.eval_do(handler, catch_env)
+            },
+            result => result,
+        }
+    }
+
+    /// `(->> value (f a) g)` is `(g (f a value))`: the value goes through
+    /// each form in turn as its last argument, so that collection functions
+    /// like `map` and `filter` can be chained, as in
+    /// `(->> (ls-data ".") (filter dir?) (map :name))`. `(-> value (f a) g)`
+    /// is `(g (f value a))`, with the value as the first argument instead,
+    /// which suits functions like `get` that take the collection first.
+    fn eval_thread(&self, name: &str, args: &[LispValue], env: Env) -> Result<LispValue> {
+        let (first, forms) = match args.split_first() {
+            Some(split) => split,
+            None => return Err(bad_syntax(name, "expected a value")),
+        };
+
+        let mut value = self.eval(first.clone(), env.clone())?;
+
+        for form in forms {
+            let (function, mut arguments) = match **form {
+                LispType::List(ref list) => match list.split_first() {
+                    Some((head, tail)) => (self.eval(head.clone(), env.clone())?, self.eval_list(tail, env.clone())?),
+                    None => return Err(Error::ApplyEmptyList),
+                },
+                _ => (self.eval(form.clone(), env.clone())?, vec![]),
+            };
+
+            if name == "->" {
+                arguments.insert(0, value);
+            } else {
+                arguments.push(value);
+            }
+            value = self.apply_function(function, &arguments, &env)?;
         }
+
+        Ok(value)
     }
 
     fn eval_do(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
@@ -92,21 +284,25 @@ impl Evaluator {
         fn validate_and_convert_param(param: &LispValue) -> Result<String> {
             match **param {
                 types::LispType::Symbol(ref name) => Ok(name.to_owned()),
-                _ => Err(Error::TypeError),
+                _ => Err(bad_syntax("fn", "parameters must be symbols")),
             }
         }
 
         match *param_list {
-            types::LispType::List(ref values) => values.iter().map(validate_and_convert_param).collect(),
-            _ => Err(Error::TypeError),
+            types::LispType::List(ref values) | types::LispType::Vector(ref values) => values.iter().map(validate_and_convert_param).collect(),
+            _ => Err(bad_syntax("fn", "expected a parameter list")),
         }
     }
 
     fn eval_fn(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
-        let params = self.validate_and_convert_param_list(args[0].clone())?;
-        let body = args[1].clone();
+        match args {
+            &[ref params, ref body] => {
+                let params = self.validate_and_convert_param_list(params.clone())?;
 
-        Ok(types::function(params, body, env))
+                Ok(types::function(params, body.clone(), env))
+            },
+            _ => Err(bad_syntax("fn", "expected a parameter list and a body")),
+        }
     }
 
     fn eval_function(&self, list: &[LispValue], env: Env) -> Result<LispValue> {
@@ -116,32 +312,66 @@ impl Evaluator {
             }
             &[ref head, ref tail @ ..] => {
                 let evaluated_head = self.eval(head.clone(), env.clone())?;
-                let evaluated_tail = self.eval_list(tail, env)?;
+                let evaluated_tail = self.eval_list(tail, env.clone())?;
 
-                match *evaluated_head {
-                    LispType::NativeFunction(ref data) => {
-                        (data.body)(&evaluated_tail)
-                    }
-                    LispType::Function(ref data) => {
-                        let body = data.body.clone();
-                        let env = env_new(Some(data.env.clone()));
+                self.apply_function(evaluated_head, &evaluated_tail, &env)
+            }
+        }
+    }
 
-                        for (name, argument) in data.params.iter().zip(evaluated_tail.iter()) {
-                            env_set(&env, name, argument.clone());
-                        }
+    /// Calls `function` with `args`, where natives see `env` as the
+    /// environment they're called from.
+    pub fn apply_function(&self, function: LispValue, args: &[LispValue], env: &Env) -> Result<LispValue> {
+        match *function {
+            LispType::NativeFunction(ref data) => {
+                let context = CallContext {
+                    evaluator: self,
+                    env: env,
+                };
 
-                        self.eval(body, env)
-                    }
-                    _ => {
-                        Err(Error::ApplyNonFunction(evaluated_head.clone()))
-                    }
+                (data.body)(args, &context)
+            }
+            LispType::Function(ref data) => {
+                Arity::Exactly(data.params.len()).check(&data.display_name(), args.len())?;
+
+                let body = data.body.clone();
+                let env = env_new(Some(data.env.clone()));
+
+                for (name, argument) in data.params.iter().zip(args.iter()) {
+                    env_set(&env, name, argument.clone());
                 }
+
+                self.eval(body, env)
+            }
+            LispType::Keyword(ref name) => {
+                self.apply_keyword(name, args)
+            }
+            _ => {
+                Err(Error::ApplyNonFunction(function.clone()))
             }
         }
     }
 
+    /// `(:key map)` looks the keyword up in the map, like `(get map :key)`.
+    fn apply_keyword(&self, name: &str, args: &[LispValue]) -> Result<LispValue> {
+        Arity::Between(1, 2).check(&format!(":{}", name), args.len())?;
+
+        let default = args.get(1).cloned().unwrap_or_else(types::nil);
+
+        match args.first().map(|arg| &**arg) {
+            Some(&LispType::HashMap(ref data)) => {
+                Ok(data.get(&HashKey::Keyword(name.to_owned())).cloned().unwrap_or(default))
+            },
+            Some(&LispType::Nil) => Ok(default),
+            _ => Err(Error::TypeError),
+        }
+    }
+
     fn eval_eval(&self, args: &[LispValue], env: Env) -> Result<LispValue> {
-        let ast = self.eval(args[0].clone(), env.clone())?;
+        let ast = match args {
+            &[ref ast] => self.eval(ast.clone(), env.clone())?,
+            _ => return Err(bad_syntax("eval", "expected 1 form")),
+        };
 
         self.eval(ast, env.clone())
     }
@@ -149,11 +379,23 @@ impl Evaluator {
     fn eval_ast(&self, lisp_expr: LispValue, env: Env) -> Result<LispValue> {
         match *lisp_expr {
             LispType::Symbol(ref s) => {
-                env_get(&env, s)
+                lookup(&env, s)
             },
             LispType::List(ref list) => {
                 Ok(types::list(self.eval_list(list, env)?))
             },
+            LispType::Vector(ref vector) => {
+                Ok(types::vector(self.eval_list(vector, env)?))
+            },
+            LispType::HashMap(ref data) => {
+                let mut evaluated = HashMap::new();
+
+                for (key, value) in data {
+                    evaluated.insert(key.clone(), self.eval(value.clone(), env.clone())?);
+                }
+
+                Ok(types::hash_map(evaluated))
+            },
             _ => Ok(lisp_expr.clone()),
         }
     }
@@ -171,6 +413,8 @@ impl Evaluator {
 
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
     use env::{env_new, env_set};
     use super::*;
     use core;
@@ -335,4 +579,24 @@ mod tests {
             types::integer(3)
         );
     }
+
+    #[test]
+    fn apply_native_closure() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+
+        let function = types::native_function(move |_, ctx| {
+            counter.set(counter.get() + 1);
+            env_get(ctx.env, "x")
+        });
+
+        let env = env_new(None);
+        env_set(&env, "x", types::integer(3));
+
+        assert_eq!(Evaluator::new().apply_function(function.clone(), &[], &env).unwrap(), types::integer(3));
+        assert_eq!(calls.get(), 1);
+
+        assert_eq!(function, function.clone());
+        assert!(function != types::native_function(|_, _| Ok(types::nil())));
+    }
 }
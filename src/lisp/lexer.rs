@@ -21,6 +21,11 @@ named!(lex_token<&str, Token>,
     alt_complete!(
 	lex_lparen |
 	lex_rparen |
+	lex_lbracket |
+	lex_rbracket |
+	lex_lbrace |
+	lex_rbrace |
+	lex_at |
 	lex_ident
     )
 );
@@ -33,9 +38,29 @@ named!(lex_rparen<&str, Token>,
     do_parse!(tag!(")") >> (Token::RParen))
 );
 
+named!(lex_lbracket<&str, Token>,
+    do_parse!(tag!("[") >> (Token::LBracket))
+);
+
+named!(lex_rbracket<&str, Token>,
+    do_parse!(tag!("]") >> (Token::RBracket))
+);
+
+named!(lex_lbrace<&str, Token>,
+    do_parse!(tag!("{") >> (Token::LBrace))
+);
+
+named!(lex_rbrace<&str, Token>,
+    do_parse!(tag!("}") >> (Token::RBrace))
+);
+
+named!(lex_at<&str, Token>,
+    do_parse!(tag!("@") >> (Token::At))
+);
+
 named!(lex_ident<&str, Token>,
     do_parse!(
-	w: re_capture!(r#"^((?:[[:word:]]|/|-|\+|\*|%|=|\.)+)|^("(?:\\.|[^\\"])*")"#) >>
+	w: re_capture!(r#"^((?:[[:word:]]|/|-|\+|\*|%|=|\.|\?|!|:|<|>)+)|^("(?:\\.|[^\\"])*")|^(\\(?:[[:alpha:]]+|.))"#) >>
 	(Token::Ident(w[0].to_owned()))
     )
 );
@@ -49,6 +74,12 @@ mod tests {
         assert_eq!(tokenize("ls").unwrap(), vec![Token::Ident("ls".to_owned())]);
     }
 
+    #[test]
+    fn lex_ident_arrow() {
+        assert_eq!(tokenize("->").unwrap(), vec![Token::Ident("->".to_owned())]);
+        assert_eq!(tokenize("->>").unwrap(), vec![Token::Ident("->>".to_owned())]);
+    }
+
     #[test]
     fn lex_ident_with_slash() {
         assert_eq!(tokenize("/bin/echo").unwrap(),
@@ -101,6 +132,41 @@ mod tests {
         assert_eq!(tokenize(")").unwrap(), vec!(Token::RParen));
     }
 
+    #[test]
+    fn lex_ident_with_question_mark() {
+        assert_eq!(tokenize("empty?").unwrap(), vec![Token::Ident("empty?".to_owned())]);
+    }
+
+    #[test]
+    fn lex_keyword() {
+        assert_eq!(tokenize(":name").unwrap(), vec![Token::Ident(":name".to_owned())]);
+    }
+
+    #[test]
+    fn lex_chars() {
+        assert_eq!(tokenize("\\a \\( \\space").unwrap(),
+                   vec![Token::Ident("\\a".to_owned()),
+                        Token::Ident("\\(".to_owned()),
+                        Token::Ident("\\space".to_owned())]);
+    }
+
+    #[test]
+    fn lex_at() {
+        assert_eq!(tokenize("@a reset!").unwrap(),
+                   vec![Token::At, Token::Ident("a".to_owned()), Token::Ident("reset!".to_owned())]);
+    }
+
+    #[test]
+    fn lex_brackets() {
+        assert_eq!(tokenize("[a]").unwrap(),
+                   vec![Token::LBracket, Token::Ident("a".to_owned()), Token::RBracket]);
+    }
+
+    #[test]
+    fn lex_braces() {
+        assert_eq!(tokenize("{}").unwrap(), vec![Token::LBrace, Token::RBrace]);
+    }
+
     #[test]
     fn lex_illegal() {
         match tokenize("^").unwrap_err() {
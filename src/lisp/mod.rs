@@ -1,6 +1,5 @@
-mod evaluator;
-mod token;
-
+pub mod evaluator;
 pub mod lexer;
+pub mod token;
 pub mod parser;
 pub mod engine;
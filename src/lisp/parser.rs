@@ -1,6 +1,6 @@
 extern crate regex;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use lisp::token::Token;
 use error::Result;
@@ -12,14 +12,19 @@ lazy_static! {
     static ref STRING_REGEX: regex::Regex = regex::Regex::new(r#"^".*"$"#).unwrap();
 }
 
+/// Deepest nesting of lists, vectors, maps and `@` the reader accepts.
+const MAX_DEPTH: usize = 256;
+
 pub struct Parser {
     tokens: VecDeque<Token>,
+    depth: usize,
 }
 
 impl Parser {
     pub fn new() -> Parser {
         Parser {
             tokens: VecDeque::new(),
+            depth: 0,
         }
     }
 
@@ -31,6 +36,18 @@ impl Parser {
         self.read()
     }
 
+    /// Reads all the remaining forms, failing on an incomplete form or a
+    /// closing delimiter without an opening one.
+    pub fn parse_all(&mut self) -> Result<Vec<types::LispValue>> {
+        let mut forms = vec![];
+
+        while self.peek().is_some() {
+            forms.push(self.read()?);
+        }
+
+        Ok(forms)
+    }
+
     fn next(&mut self) -> Option<Token> {
         self.tokens.pop_front()
     }
@@ -57,13 +74,36 @@ impl Parser {
     }
 
     fn read(&mut self) -> Result<types::LispValue> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::DepthExceeded);
+        }
+
+        self.depth += 1;
+        let result = self.read_form();
+        self.depth -= 1;
+
+        result
+    }
+
+    fn read_form(&mut self) -> Result<types::LispValue> {
         let token = self.peek();
 
         match token {
             Some(token) => {
                 match token {
                     Token::LParen => {
-                        Ok(self.read_list()?)
+                        Ok(types::list(self.read_seq(Token::LParen, Token::RParen)?))
+                    }
+                    Token::LBracket => {
+                        Ok(types::vector(self.read_seq(Token::LBracket, Token::RBracket)?))
+                    }
+                    Token::LBrace => {
+                        Ok(self.read_hash_map()?)
+                    }
+                    Token::At => {
+                        self.next();
+
+                        Ok(types::list(vec![types::symbol("deref".to_owned()), self.read()?]))
                     }
                     _ => {
                         Ok(self.read_atom()?)
@@ -76,8 +116,8 @@ impl Parser {
         }
     }
 
-    fn read_list(&mut self) -> Result<types::LispValue> {
-        self.expect(Token::LParen)?;
+    fn read_seq(&mut self, open: Token, close: Token) -> Result<Vec<types::LispValue>> {
+        self.expect(open)?;
 
         let mut forms = vec![];
 
@@ -86,14 +126,11 @@ impl Parser {
 
             match token {
                 Some(token) => {
-                    match token {
-                        Token::RParen => {
-                            break;
-                        }
-                        _ => {
-                            forms.push(self.read()?);
-                        }
+                    if token == close {
+                        break;
                     }
+
+                    forms.push(self.read()?);
                 }
                 None => {
                     return Err(Error::ParseError);
@@ -101,9 +138,26 @@ impl Parser {
             }
         }
 
-        self.expect(Token::RParen)?;
+        self.expect(close)?;
 
-        Ok(types::list(forms))
+        Ok(forms)
+    }
+
+    fn read_hash_map(&mut self) -> Result<types::LispValue> {
+        let forms = self.read_seq(Token::LBrace, Token::RBrace)?;
+
+        if forms.len() % 2 != 0 {
+            return Err(Error::ParseError);
+        }
+
+        let mut data = HashMap::new();
+
+        for entry in forms.chunks(2) {
+            let key = types::HashKey::from_value(&entry[0]).map_err(|_| Error::ParseError)?;
+            data.insert(key, entry[1].clone());
+        }
+
+        Ok(types::hash_map(data))
     }
 
     fn read_atom(&mut self) -> Result<types::LispValue> {
@@ -112,12 +166,20 @@ impl Parser {
         match token {
             Some(Token::Ident(token)) => {
                 if INTEGER_REGEX.is_match(&token) {
-                    let value: i64 = token.parse().unwrap();
+                    let value: i64 = token.parse().map_err(|_| Error::IntegerOverflow)?;
                     Ok(types::integer(value))
                 } else if token == "nil" {
                     Ok(types::nil())
+                } else if token == "true" {
+                    Ok(types::boolean(true))
+                } else if token == "false" {
+                    Ok(types::boolean(false))
                 } else if STRING_REGEX.is_match(&token) {
                     Ok(types::string(token[1..token.len()-1].to_owned()))
+                } else if token.starts_with(':') && token.len() > 1 {
+                    Ok(types::keyword(token[1..].to_owned()))
+                } else if token.starts_with('\\') {
+                    Ok(types::character(read_char(&token[1..])?))
                 } else {
                     Ok(types::symbol(token))
                 }
@@ -133,6 +195,20 @@ impl Parser {
     }
 }
 
+/// A single character or the name of one, like `space`.
+fn read_char(text: &str) -> Result<char> {
+    let mut chars = text.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(c);
+    }
+
+    match types::CHAR_NAMES.iter().find(|&&(name, _)| name == text) {
+        Some(&(_, c)) => Ok(c),
+        None => Err(Error::ParseError),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use types::*;
@@ -235,4 +311,106 @@ mod tests {
 
         assert_input_with_ast(input, expected);
     }
+
+    #[test]
+    fn parse_booleans() {
+        assert_input_with_ast("(true false)", types::list(vec![types::boolean(true), types::boolean(false)]));
+    }
+
+    #[test]
+    fn parse_vector() {
+        let input = "[1 (a) []]";
+        let expected = types::vector(
+            vec![
+                types::integer(1),
+                types::list(vec![types::symbol("a".to_owned())]),
+                types::vector(vec![]),
+            ]
+        );
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_unbalanced_vector() {
+        assert!(parse("[1 2)").is_err());
+    }
+
+    #[test]
+    fn parse_hash_map() {
+        let input = "{\"a\" (b) 1 nil}";
+        let expected = types::hash_map(
+            hashmap!{
+                HashKey::from("a") => types::list(vec![types::symbol("b".to_owned())]),
+                HashKey::Integer(1) => types::nil(),
+            }
+        );
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_invalid_hash_map() {
+        assert!(parse("{\"a\"}").is_err());
+        assert!(parse("{a 1}").is_err());
+    }
+
+    #[test]
+    fn parse_keyword() {
+        assert_input_with_ast("(:a :b-c)", types::list(vec![types::keyword("a".to_owned()), types::keyword("b-c".to_owned())]));
+    }
+
+    #[test]
+    fn parse_chars() {
+        assert_input_with_ast("(\\a \\) \\newline)",
+                              types::list(vec![types::character('a'), types::character(')'), types::character('\n')]));
+        assert!(parse("\\unknown").is_err());
+    }
+
+    #[test]
+    fn parse_deref_shorthand() {
+        let input = "@(a)";
+        let expected = types::list(
+            vec![
+                types::symbol("deref".to_owned()),
+                types::list(vec![types::symbol("a".to_owned())]),
+            ]
+        );
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_deep_nesting() {
+        assert!(parse(&format!("{}{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH))).is_ok());
+
+        for input in &["(".repeat(100_000), "[{".repeat(50_000), format!("{}a", "@".repeat(100_000))] {
+            match parse(input) {
+                Err(Error::DepthExceeded) => {},
+                result => panic!("expected a depth error, got {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_all_forms() {
+        let mut parser = Parser::new();
+        parser.add_tokens(tokenize("1 (a) \"b\"").unwrap());
+
+        assert_eq!(parser.parse_all().unwrap(), vec![
+            types::integer(1),
+            types::list(vec![types::symbol("a".to_owned())]),
+            types::string("b".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn parse_all_unbalanced() {
+        for input in &["1) (def x 2", "(a", ")"] {
+            let mut parser = Parser::new();
+            parser.add_tokens(tokenize(input).unwrap());
+
+            assert!(parser.parse_all().is_err(), "{}", input);
+        }
+    }
 }
@@ -2,5 +2,10 @@
 pub enum Token {
     LParen,
     RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    At,
     Ident(String),
 }
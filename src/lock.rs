@@ -0,0 +1,38 @@
+extern crate libc;
+
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use error::{Error, Result};
+
+/// Advisory `flock` on a file, released when dropped.
+pub struct FileLock {
+    fd: RawFd,
+}
+
+impl FileLock {
+    pub fn shared(file: &File) -> Result<FileLock> {
+        FileLock::acquire(file, libc::LOCK_SH)
+    }
+
+    pub fn exclusive(file: &File) -> Result<FileLock> {
+        FileLock::acquire(file, libc::LOCK_EX)
+    }
+
+    fn acquire(file: &File, operation: libc::c_int) -> Result<FileLock> {
+        let fd = file.as_raw_fd();
+
+        if unsafe { libc::flock(fd, operation) } != 0 {
+            return Err(Error::IoError(io::Error::last_os_error()));
+        }
+
+        Ok(FileLock { fd: fd })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        unsafe { libc::flock(self.fd, libc::LOCK_UN) };
+    }
+}
@@ -1,9 +1,48 @@
 extern crate lish;
 
-use lish::repl::Repl;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use lish::repl::{Options, Repl};
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("lish: {}", message);
+    eprintln!("usage: lish [--login] [--norc | --rc FILE] [--stdlib DIR] [--json-errors]");
+    process::exit(2);
+}
+
+fn parse_options() -> Options {
+    let mut options = Options::default();
+    let mut args = env::args();
+
+    // login programs start shells with a name starting with `-`
+    if let Some(name) = args.next() {
+        options.login = name.starts_with('-');
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" | "--login" => options.login = true,
+            "--norc" => options.norc = true,
+            "--json-errors" => options.json_errors = true,
+            "--rc" => match args.next() {
+                Some(file) => options.rc_file = Some(PathBuf::from(file)),
+                None => usage_error("--rc requires a file"),
+            },
+            "--stdlib" => match args.next() {
+                Some(dir) => options.stdlib_dir = Some(PathBuf::from(dir)),
+                None => usage_error("--stdlib requires a directory"),
+            },
+            _ => usage_error(&format!("unknown option: {}", arg)),
+        }
+    }
+
+    options
+}
 
 fn main() {
-    let mut repl = Repl::new();
+    let mut repl = Repl::with_options(parse_options());
 
     repl.run();
 }
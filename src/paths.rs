@@ -0,0 +1,60 @@
+use std::env;
+use std::env::home_dir;
+use std::path::PathBuf;
+
+/// `$XDG_DATA_HOME/lish/<name>` when XDG_DATA_HOME is set, `~/<dotfile>` otherwise.
+pub fn data_file(name: &str, dotfile: &str) -> Option<PathBuf> {
+    match env::var("XDG_DATA_HOME") {
+        Ok(ref dir) if !dir.is_empty() => {
+            let mut path = PathBuf::from(dir);
+            path.push("lish");
+            path.push(name);
+            Some(path)
+        },
+        _ => {
+            home_dir().map(|mut path| {
+                path.push(dotfile);
+                path
+            })
+        },
+    }
+}
+
+/// `$XDG_CONFIG_HOME/lish` when XDG_CONFIG_HOME is set, `~/.config/lish` otherwise.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match env::var("XDG_CONFIG_HOME") {
+        Ok(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|mut path| {
+            path.push(".config");
+            path
+        }),
+    };
+
+    base.map(|mut path| {
+        path.push("lish");
+        path
+    })
+}
+
+/// Replaces a leading `~` with the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    if path == "~" || path.starts_with("~/") {
+        if let Some(mut home) = home_dir() {
+            if path.len() > 2 {
+                home.push(&path[2..]);
+            }
+            return home;
+        }
+    }
+
+    PathBuf::from(path)
+}
+
+/// The first file that exists among `<name>` in the config directory and
+/// `~/<dotfile>`.
+pub fn config_file(name: &str, dotfile: &str) -> Option<PathBuf> {
+    let config = config_dir().map(|dir| dir.join(name));
+    let dotfile = home_dir().map(|home| home.join(dotfile));
+
+    config.into_iter().chain(dotfile).find(|path| path.is_file())
+}
@@ -1,17 +1,25 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 
-use types::{LispType, LispValue};
+use types::{self, AtomType, HashKey, LispType, LispValue};
 
-pub struct Printer;
+pub struct Printer {
+    /// Atoms being printed, so that one containing itself is printed as
+    /// `#<atom ...>` the second time instead of forever.
+    atoms: RefCell<Vec<*const AtomType>>,
+}
 
 impl Printer {
     pub fn new() -> Printer {
-        Printer
+        Printer {
+            atoms: RefCell::new(vec![]),
+        }
     }
 
     pub fn print(&self, lisp_expr: &LispValue, readable: bool) -> String {
         match **lisp_expr {
             LispType::Nil => "nil".to_owned(),
+            LispType::Bool(b) => b.to_string(),
             LispType::Integer(i) => i.to_string(),
             LispType::Strn(ref s) => {
                 if readable {
@@ -21,18 +29,42 @@ impl Printer {
                 }
             },
             LispType::Symbol(ref s) => s.clone(),
+            LispType::Keyword(ref name) => format!(":{}", name),
+            LispType::Char(c) => {
+                if readable {
+                    print_char(c)
+                } else {
+                    c.to_string()
+                }
+            },
             LispType::Function(_) => "#<function ...>".to_owned(),
             LispType::NativeFunction(_) => "#<native-function ...>".to_owned(),
-            LispType::List(ref exprs) => self.print_list(exprs, readable),
+            LispType::List(ref exprs) => self.print_seq(exprs, "(", ")", readable),
+            LispType::Vector(ref exprs) => self.print_seq(exprs, "[", "]", readable),
             LispType::HashMap(ref data) => self.print_hashmap(data, readable),
+            LispType::Atom(ref atom) => self.print_atom(atom, readable),
+        }
+    }
+
+    fn print_atom(&self, atom: &AtomType, readable: bool) -> String {
+        let id = atom as *const AtomType;
+
+        if self.atoms.borrow().contains(&id) {
+            return "#<atom ...>".to_owned();
         }
+
+        self.atoms.borrow_mut().push(id);
+        let value = self.print(&atom.value.borrow(), readable);
+        self.atoms.borrow_mut().pop();
+
+        format!("#<atom {}>", value)
     }
 
-    fn print_list(&self, exprs: &Vec<LispValue>, readable: bool) -> String {
+    fn print_seq(&self, exprs: &[LispValue], open: &str, close: &str, readable: bool) -> String {
         let mut first = true;
         let mut res = String::new();
 
-        res.push_str("(");
+        res.push_str(open);
 
         for expr in exprs.iter() {
             if first {
@@ -42,22 +74,24 @@ impl Printer {
             }
             res.push_str(&self.print(expr, readable));
         }
-        res.push_str(")");
+        res.push_str(close);
         res
     }
 
-    fn print_hashmap(&self, data: &HashMap<String, LispValue>, readable: bool) -> String {
+    /// Prints the entries sorted by key, with keys always in readable form.
+    fn print_hashmap(&self, data: &HashMap<HashKey, LispValue>, readable: bool) -> String {
         let mut res = String::new();
 
         res.push_str("{");
 
-        for (name, value) in data {
+        for (key, value) in types::sorted_entries(data) {
             res.push_str(" ");
-            res.push_str("\"");
-            res.push_str(&name);
-            res.push_str("\"");
+            res.push_str(&self.print(&key.to_value(), true));
             res.push_str(" ");
             res.push_str(&self.print(value, readable));
+        }
+
+        if !data.is_empty() {
             res.push_str(" ");
         }
         res.push_str("}");
@@ -65,14 +99,21 @@ impl Printer {
     }
 }
 
+fn print_char(c: char) -> String {
+    match types::CHAR_NAMES.iter().find(|&&(_, named)| named == c) {
+        Some(&(name, _)) => format!("\\{}", name),
+        None => format!("\\{}", c),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use types;
+    use types::{self, CallContext};
     use super::*;
     use error::Result;
     use env;
 
-    fn mock_func(_: &[LispValue]) -> Result<LispValue> {
+    fn mock_func(_: &[LispValue], _: &CallContext) -> Result<LispValue> {
         Ok(types::integer(3))
     }
 
@@ -159,6 +200,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn print_keyword() {
+        assert_eq!(print(&types::keyword("name".to_owned())), ":name");
+    }
+
+    #[test]
+    fn print_chars() {
+        assert_eq!(print(&types::character('a')), "\\a");
+        assert_eq!(print(&types::character(' ')), "\\space");
+        assert_eq!(display(&types::character(' ')), " ");
+    }
+
+    #[test]
+    fn print_atom() {
+        assert_eq!(print(&types::atom(types::string("a".to_owned()))), "#<atom \"a\">");
+    }
+
+    #[test]
+    fn print_atom_containing_itself() {
+        let atom = types::atom(types::nil());
+        if let LispType::Atom(ref data) = *atom {
+            *data.value.borrow_mut() = atom.clone();
+        }
+
+        assert_eq!(print(&atom), "#<atom #<atom ...>>");
+    }
+
+    #[test]
+    fn print_booleans() {
+        assert_eq!(print(&types::boolean(true)), "true");
+        assert_eq!(print(&types::boolean(false)), "false");
+    }
+
+    #[test]
+    fn print_vector() {
+        assert_eq!(
+            print(
+                &types::vector(
+                    vec![
+                        types::integer(1),
+                        types::vector(vec![]),
+                        types::string("a".to_owned())
+                    ]
+                )
+            ),
+            "[1 [] \"a\"]"
+        );
+    }
+
     #[test]
     fn print_empty_hashmap() {
         assert_eq!(
@@ -177,7 +267,7 @@ mod tests {
             print(
                 &types::hash_map(
                     hashmap!{
-                        "a".to_owned() => types::integer(1),
+                        HashKey::from("a") => types::integer(1),
                     }
                 )
             ),
@@ -185,6 +275,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn print_hashmap_sorted_by_key() {
+        assert_eq!(
+            print(
+                &types::hash_map(
+                    hashmap!{
+                        HashKey::from("b") => types::integer(2),
+                        HashKey::Integer(10) => types::string("ten".to_owned()),
+                        HashKey::from("a") => types::integer(1),
+                        HashKey::Integer(9) => types::nil(),
+                    }
+                )
+            ),
+            "{ 9 nil 10 \"ten\" \"a\" 1 \"b\" 2 }"
+        );
+    }
+
     #[test]
     fn print_native_function() {
         assert_eq!(
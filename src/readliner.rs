@@ -1,24 +1,51 @@
 extern crate libc;
 extern crate rustyline;
 
+use std::borrow::Cow;
+use std::env::current_dir;
 use std::io;
+use std::path::Path;
 
+use self::rustyline::Context;
 use self::rustyline::Editor;
+use self::rustyline::Helper;
+use self::rustyline::completion::Completer;
+use self::rustyline::hint::Hinter;
+use self::rustyline::highlight::Highlighter;
+use self::rustyline::validate::{Validator, ValidationContext, ValidationResult};
 
-use error::Result;
+use error::{Error, Result};
+use env::{Env, env_get};
+use history::Entry;
+use types::{self, HashKey, LispType};
+use lisp::evaluator::{is_special_form, lookup};
+use lisp::lexer::tokenize as lisp_tokenize;
+use lisp::token::Token as LispToken;
+use shell::engine::command_exists;
+use shell::heredoc;
+use shell::lexer::tokenize as shell_tokenize;
+use shell::token::Token as ShellToken;
+use shell::word::split_assignment;
+
+lazy_static! {
+    static ref INTEGER_REGEX: ::regex::Regex = ::regex::Regex::new(r"^-?[0-9]+$").unwrap();
+}
 
 pub trait Readliner {
     fn readline(&mut self) -> Result<String>;
-    fn add_history_entry(&mut self, line: &str) -> bool;
+    fn add_history_entry(&mut self, entry: &Entry) -> bool;
 }
 
 pub struct RustylineReadliner {
-    editor: Editor<()>,
+    editor: Editor<LishHelper>,
 }
 
 impl RustylineReadliner {
-    pub fn new() -> RustylineReadliner {
-        RustylineReadliner { editor: Editor::<()>::new() }
+    pub fn new(env: Env) -> RustylineReadliner {
+        let mut editor = Editor::<LishHelper>::new();
+        editor.set_helper(Some(LishHelper { env: env, history: vec![] }));
+
+        RustylineReadliner { editor: editor }
     }
 }
 
@@ -27,9 +54,339 @@ impl Readliner for RustylineReadliner {
         Ok(self.editor.readline(":) ")?)
     }
 
-    fn add_history_entry(&mut self, line: &str) -> bool {
-        self.editor.add_history_entry(line)
+    fn add_history_entry(&mut self, entry: &Entry) -> bool {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.history.push(entry.clone());
+        }
+
+        self.editor.add_history_entry(entry.command.as_str())
+    }
+}
+
+/// Colours, validates and suggests completions for the line being edited.
+///
+/// Colours are ANSI SGR codes looked up in the `COLORS` hash on every
+/// keystroke, so redefining it takes effect immediately.
+pub struct LishHelper {
+    env: Env,
+    history: Vec<Entry>,
+}
+
+impl Helper for LishHelper {}
+
+impl Completer for LishHelper {
+    type Candidate = String;
+}
+
+impl Hinter for LishHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _: &Context) -> Option<String> {
+        if pos < line.len() || !autosuggestions_enabled(&self.env) {
+            return None;
+        }
+
+        let cwd = match current_dir() {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(_) => String::new(),
+        };
+
+        suggest(&self.history, line, &cwd)
+    }
+}
+
+impl Highlighter for LishHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        let highlighted = if is_lisp(line) {
+            highlight_lisp(line, pos, &self.env)
+        } else {
+            highlight_shell(line, &self.env)
+        };
+
+        match highlighted {
+            Some(value) => Cow::Owned(value),
+            None => Cow::Borrowed(line),
+        }
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(paint(hint, "hint", &self.env))
+    }
+
+    fn highlight_char(&self, _: &str, _: usize) -> bool {
+        true
+    }
+}
+
+impl Validator for LishHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+
+        let complete = if is_lisp(input) {
+            is_complete_lisp(input)
+        } else {
+            heredoc::is_complete(input)
+        };
+
+        if !complete {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+/// Suggestions are on unless `AUTOSUGGEST` is bound to nil.
+fn autosuggestions_enabled(env: &Env) -> bool {
+    match env_get(env, "AUTOSUGGEST") {
+        Ok(value) => types::is_truthy(&value),
+        Err(_) => true,
+    }
+}
+
+/// Rest of the most recent single line entry starting with `prefix`, preferring
+/// entries that were run in `cwd`.
+fn suggest(history: &[Entry], prefix: &str, cwd: &str) -> Option<String> {
+    if prefix.is_empty() {
+        return None;
+    }
+
+    let candidates = || {
+        history.iter().rev().filter(|entry| {
+            entry.command.len() > prefix.len() &&
+                entry.command.starts_with(prefix) &&
+                !entry.command.contains('\n')
+        })
+    };
+
+    candidates()
+        .find(|entry| entry.cwd == cwd)
+        .or_else(|| candidates().next())
+        .map(|entry| entry.command[prefix.len()..].to_owned())
+}
+
+fn is_lisp(line: &str) -> bool {
+    line.starts_with('(')
+}
+
+/// Whether every paren, bracket, brace and string in `input` has been closed.
+pub fn is_complete_lisp(input: &str) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => { chars.next(); },
+                '"' => in_string = false,
+                _ => {},
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '\\' => { chars.next(); },
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {},
+            }
+        }
+    }
+
+    depth <= 0 && !in_string
+}
+
+fn default_color(name: &str) -> Option<&'static str> {
+    match name {
+        "matching-paren" => Some("1;34"),
+        "unmatched-paren" => Some("1;31"),
+        "string" => Some("32"),
+        "keyword" => Some("35"),
+        "number" => Some("33"),
+        "known-symbol" => Some("36"),
+        "unknown-symbol" => Some("31"),
+        "command" => Some("32"),
+        "unknown-command" => Some("31"),
+        "path" => Some("4"),
+        "hint" => Some("90"),
+        _ => None,
+    }
+}
+
+/// SGR code for `name`, from `COLORS` when it has an entry, the default otherwise.
+fn color(name: &str, env: &Env) -> Option<String> {
+    if let Ok(colors) = env_get(env, "COLORS") {
+        if let LispType::HashMap(ref data) = *colors {
+            if let Some(value) = data.get(&HashKey::from(name)) {
+                return match **value {
+                    LispType::Strn(ref code) => Some(code.to_owned()),
+                    _ => None,
+                };
+            }
+        }
+    }
+
+    default_color(name).map(|code| code.to_owned())
+}
+
+fn paint(text: &str, name: &str, env: &Env) -> String {
+    match color(name, env) {
+        Some(code) => format!("\x1b[{}m{}\x1b[0m", code, text),
+        None => text.to_owned(),
+    }
+}
+
+/// Byte ranges of `texts` in `line`, which are expected to appear in order.
+fn spans(line: &str, texts: &[String]) -> Option<Vec<(usize, usize)>> {
+    let mut offset = 0;
+    let mut result = vec![];
+
+    for text in texts {
+        let start = offset + line[offset..].find(text.as_str())?;
+        offset = start + text.len();
+        result.push((start, offset));
+    }
+
+    Some(result)
+}
+
+/// Concatenates `line` with each span replaced by its painted version.
+fn assemble(line: &str, spans: &[(usize, usize)], painted: Vec<String>) -> String {
+    let mut result = String::new();
+    let mut offset = 0;
+
+    for (&(start, end), text) in spans.iter().zip(painted) {
+        result.push_str(&line[offset..start]);
+        result.push_str(&text);
+        offset = end;
     }
+
+    result.push_str(&line[offset..]);
+    result
+}
+
+fn highlight_lisp(line: &str, pos: usize, env: &Env) -> Option<String> {
+    let tokens = lisp_tokenize(line).ok()?;
+
+    let texts: Vec<String> = tokens.iter().map(|token| {
+        match *token {
+            LispToken::LParen => "(".to_owned(),
+            LispToken::RParen => ")".to_owned(),
+            LispToken::LBracket => "[".to_owned(),
+            LispToken::RBracket => "]".to_owned(),
+            LispToken::LBrace => "{".to_owned(),
+            LispToken::RBrace => "}".to_owned(),
+            LispToken::At => "@".to_owned(),
+            LispToken::Ident(ref value) => value.to_owned(),
+        }
+    }).collect();
+    let spans = spans(line, &texts)?;
+
+    let mut partner: Vec<Option<usize>> = vec![None; tokens.len()];
+    let mut open = vec![];
+    for (i, token) in tokens.iter().enumerate() {
+        match *token {
+            LispToken::LParen | LispToken::LBracket | LispToken::LBrace => open.push(i),
+            LispToken::RParen | LispToken::RBracket | LispToken::RBrace => {
+                if let Some(j) = open.pop() {
+                    partner[i] = Some(j);
+                    partner[j] = Some(i);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let cursor_paren = (0..tokens.len())
+        .filter(|&i| match tokens[i] {
+            LispToken::Ident(_) | LispToken::At => false,
+            _ => true,
+        })
+        .find(|&i| spans[i].0 == pos || spans[i].1 == pos);
+
+    let painted = tokens.iter().enumerate().map(|(i, token)| {
+        match *token {
+            LispToken::Ident(ref value) => {
+                if value.starts_with('"') || value.starts_with('\\') {
+                    paint(value, "string", env)
+                } else if value.starts_with(':') {
+                    paint(value, "keyword", env)
+                } else if INTEGER_REGEX.is_match(value) || value == "nil" || value == "true" || value == "false" {
+                    paint(value, "number", env)
+                } else if is_special_form(value) || lookup(env, value).is_ok() {
+                    paint(value, "known-symbol", env)
+                } else {
+                    paint(value, "unknown-symbol", env)
+                }
+            },
+            LispToken::At => texts[i].clone(),
+            _ => {
+                match partner[i] {
+                    None => paint(&texts[i], "unmatched-paren", env),
+                    Some(j) if cursor_paren == Some(i) || cursor_paren == Some(j) => {
+                        paint(&texts[i], "matching-paren", env)
+                    },
+                    Some(_) => paint(&texts[i], "paren", env),
+                }
+            },
+        }
+    }).collect();
+
+    Some(assemble(line, &spans, painted))
+}
+
+fn highlight_shell(line: &str, env: &Env) -> Option<String> {
+    let tokens = shell_tokenize(line).ok()?;
+
+    let texts: Vec<String> = tokens.iter().map(|token| {
+        match *token {
+            ShellToken::Ident(ref value) => value.to_owned(),
+            ShellToken::Semicolon => ";".to_owned(),
+            ShellToken::And => "&&".to_owned(),
+            ShellToken::Pipe => "|".to_owned(),
+            ShellToken::RedirectInput => "<".to_owned(),
+            ShellToken::RedirectOutput => ">".to_owned(),
+            ShellToken::RedirectAppend => ">>".to_owned(),
+            ShellToken::RedirectHereDoc => "<<".to_owned(),
+            ShellToken::RedirectHereDocStrip => "<<-".to_owned(),
+            ShellToken::RedirectHereString => "<<<".to_owned(),
+            ShellToken::HereDoc { ref body, .. } => body.to_owned(),
+        }
+    }).collect();
+    let spans = spans(line, &texts)?;
+
+    let mut command_position = true;
+    let painted = tokens.iter().zip(texts.iter()).map(|(token, text)| {
+        match *token {
+            ShellToken::Ident(_) => {
+                if command_position && split_assignment(text).is_some() {
+                    return text.to_owned();
+                }
+
+                let painted = if command_position {
+                    if command_exists(text, env) {
+                        paint(text, "command", env)
+                    } else {
+                        paint(text, "unknown-command", env)
+                    }
+                } else if Path::new(text).exists() {
+                    paint(text, "path", env)
+                } else {
+                    text.to_owned()
+                };
+
+                command_position = false;
+                painted
+            },
+            ShellToken::Semicolon | ShellToken::And | ShellToken::Pipe => {
+                command_position = true;
+                text.to_owned()
+            },
+            _ => text.to_owned(),
+        }
+    }).collect();
+
+    Some(assemble(line, &spans, painted))
 }
 
 pub struct StdinReadliner;
@@ -43,23 +400,119 @@ impl StdinReadliner {
 impl Readliner for StdinReadliner {
     fn readline(&mut self) -> Result<String> {
         let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Err(Error::Eof);
+        }
         input.pop();
 
         Ok(input)
     }
 
-    fn add_history_entry(&mut self, _: &str) -> bool {
+    fn add_history_entry(&mut self, _: &Entry) -> bool {
         false
     }
 }
 
-pub fn create_readliner() -> Box<dyn Readliner> {
-    let istty = unsafe { libc::isatty(libc::STDIN_FILENO as i32) } != 0;
+pub fn is_interactive() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO as i32) != 0 }
+}
 
-    if istty {
-        Box::new(RustylineReadliner::new())
+pub fn create_readliner(env: Env) -> Box<dyn Readliner> {
+    if is_interactive() {
+        Box::new(RustylineReadliner::new(env))
     } else {
         Box::new(StdinReadliner::new())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core;
+    use env::{env_new, env_set};
+    use types;
+    use super::*;
+
+    fn entry(command: &str, cwd: &str) -> Entry {
+        Entry {
+            timestamp: 0,
+            cwd: cwd.to_owned(),
+            command: command.to_owned(),
+        }
+    }
+
+    #[test]
+    fn suggest_most_recent_match() {
+        let history = vec![entry("git status", "/a"), entry("git stash", "/a"), entry("ls", "/a")];
+
+        assert_eq!(suggest(&history, "git st", "/a"), Some("ash".to_owned()));
+        assert_eq!(suggest(&history, "cargo", "/a"), None);
+        assert_eq!(suggest(&history, "ls", "/a"), None);
+        assert_eq!(suggest(&history, "", "/a"), None);
+    }
+
+    #[test]
+    fn suggest_prefers_current_directory() {
+        let history = vec![entry("make test", "/a"), entry("make build", "/b")];
+
+        assert_eq!(suggest(&history, "make ", "/a"), Some("test".to_owned()));
+        assert_eq!(suggest(&history, "make ", "/c"), Some("build".to_owned()));
+    }
+
+    #[test]
+    fn complete_lisp() {
+        assert!(is_complete_lisp("(+ 1 2)"));
+        assert!(is_complete_lisp("(+ 1 \")\")"));
+        assert!(!is_complete_lisp("(def a (+ 1"));
+        assert!(!is_complete_lisp("(count [1 2"));
+        assert!(is_complete_lisp("(list \\( \\[)"));
+        assert!(!is_complete_lisp("(display \"abc"));
+    }
+
+    #[test]
+    fn highlight_lisp_symbols_and_literals() {
+        let env = core::env::create();
+
+        assert_eq!(
+            highlight_lisp("(+ 1 \"a\" b)", 100, &env).unwrap(),
+            "(\x1b[36m+\x1b[0m \x1b[33m1\x1b[0m \x1b[32m\"a\"\x1b[0m \x1b[31mb\x1b[0m)"
+        );
+    }
+
+    #[test]
+    fn highlight_lisp_matching_paren() {
+        let env = env_new(None);
+
+        assert_eq!(
+            highlight_lisp("(nil)", 5, &env).unwrap(),
+            "\x1b[1;34m(\x1b[0m\x1b[33mnil\x1b[0m\x1b[1;34m)\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn highlight_shell_commands_after_separators() {
+        let env = env_new(None);
+
+        assert_eq!(
+            highlight_shell("echo a | nonexistent-command", &env).unwrap(),
+            "\x1b[32mecho\x1b[0m a | \x1b[31mnonexistent-command\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn highlight_shell_command_after_assignments() {
+        let env = env_new(None);
+
+        assert_eq!(highlight_shell("A=1 echo", &env).unwrap(), "A=1 \x1b[32mecho\x1b[0m");
+    }
+
+    #[test]
+    fn configured_colors() {
+        let env = env_new(None);
+        env_set(&env, "COLORS", types::hash_map(hashmap!{
+            HashKey::from("number") => types::string("35".to_owned()),
+            HashKey::from("unmatched-paren") => types::nil(),
+        }));
+
+        assert_eq!(highlight_lisp("(1", 0, &env).unwrap(), "(\x1b[35m1\x1b[0m");
+    }
+}
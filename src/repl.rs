@@ -1,37 +1,140 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+
 use error::Error;
 use error::Result;
 
-use readliner::create_readliner;
+use readliner::{create_readliner, is_interactive};
 use readliner::Readliner;
+use history::{self, History, Entry};
 use engine::Engine;
+use shell::heredoc;
 use printer::Printer;
+use paths;
 use types::LispValue;
 
+/// Options from the command line.
+#[derive(Default)]
+pub struct Options {
+    /// Directory with standard library modules to load instead of the
+    /// embedded ones.
+    pub stdlib_dir: Option<PathBuf>,
+
+    /// Don't load any startup file.
+    pub norc: bool,
+
+    /// Startup file to load instead of `init.lish`.
+    pub rc_file: Option<PathBuf>,
+
+    /// Load the profile as a login shell does.
+    pub login: bool,
+
+    /// Print errors as JSON objects with their category, code and message.
+    pub json_errors: bool,
+}
+
 pub struct Repl {
     readliner: Box<dyn Readliner>,
+    history: Option<History>,
     engine: Engine,
     printer: Printer,
+    json_errors: bool,
 }
 
 impl Repl {
     pub fn new() -> Repl {
+        Repl::with_options(Options::default())
+    }
+
+    pub fn with_options(options: Options) -> Repl {
         let mut engine = Engine::new();
+        engine.set_json_errors(options.json_errors);
 
-        match engine.load_standard_library() {
-            Ok(()) => {},
-            Err(e) => panic!("{:?}", e),
+        let stdlib = match options.stdlib_dir {
+            Some(ref dir) => engine.load_standard_library_from(Some(dir)),
+            None => engine.load_standard_library(),
+        };
+
+        let mut repl = Repl {
+            readliner: create_readliner(engine.env()),
+            history: None,
+            engine: engine,
+            printer: Printer::new(),
+            json_errors: options.json_errors,
         };
 
-        match engine.load_initrc() {
+        if let Err(e) = stdlib {
+            repl.report(&e);
+        }
+
+        if !options.norc {
+            repl.load_startup_files(&options);
+        }
+
+        if is_interactive() {
+            if let Err(e) = repl.load_history() {
+                eprintln!("history error: {}", e);
+            }
+        }
+
+        repl
+    }
+
+    /// Loads `profile.lish` for login and non-interactive shells, then
+    /// `init.lish` for interactive ones, from the config directory or as
+    /// `~/.lish_profile.lish` and `~/.lishrc.lish`.
+    ///
+    /// A file given with `--rc` or `LISH_RC` replaces `init.lish` and is
+    /// loaded by every shell.
+    fn load_startup_files(&mut self, options: &Options) {
+        let interactive = is_interactive();
+
+        if options.login || !interactive {
+            if let Some(path) = paths::config_file("profile.lish", ".lish_profile.lish") {
+                self.load_startup_file(&path);
+            }
+        }
+
+        let rc_file = options.rc_file.clone().or_else(|| {
+            match env::var("LISH_RC") {
+                Ok(ref path) if !path.is_empty() => Some(paths::expand_home(path)),
+                _ => None,
+            }
+        });
+
+        match rc_file {
+            Some(path) => self.load_startup_file(&path),
+            None if interactive => {
+                if let Some(path) = paths::config_file("init.lish", ".lishrc.lish") {
+                    self.load_startup_file(&path);
+                }
+            },
+            None => {},
+        }
+    }
+
+    /// Errors are reported and the shell starts anyway.
+    fn load_startup_file(&mut self, path: &Path) {
+        match self.engine.load_startup_file(path) {
             Ok(()) => {},
-            Err(e) => panic!("{:?}", e),
+            Err(Error::Exit(code)) => process::exit(code),
+            Err(e) => self.report(&e),
         }
+    }
 
-        Repl {
-            readliner: create_readliner(),
-            engine: engine,
-            printer: Printer::new(),
+    fn load_history(&mut self) -> Result<()> {
+        let history = History::new();
+
+        history.truncate(history::max_size(&self.engine.env())?)?;
+
+        for entry in history.load()? {
+            self.readliner.add_history_entry(&entry);
         }
+
+        self.history = Some(history);
+
+        Ok(())
     }
 
     pub fn run(&mut self) {
@@ -43,45 +146,73 @@ impl Repl {
                 }
                 Err(Error::Interrupted) => {}
                 Err(Error::Eof) => break,
-                Err(Error::ReadlineError(e)) => {
-                    println!("readline error: {}", e);
-                }
-                Err(Error::IoError(e)) => {
-                    println!("io error: {}", e);
-                }
-                Err(Error::UnexpectedCharacter(c)) => {
-                    println!("lexer error: unexpected character `{}`", c);
-                }
-                Err(Error::ParseError) => {
-                    println!("parser error");
-                }
-                Err(Error::CommandNotFound(command)) => {
-                    println!("shell error: command not found: {}", command);
-                }
-                Err(Error::UndefinedBinding(name)) => {
-                    println!("lookup error: undefined binding: {}", name);
-                }
-                Err(Error::ApplyEmptyList) => {
-                    println!("apply error: unable to apply empty list");
-                }
-                Err(Error::ApplyNonFunction(value)) => {
-                    println!("apply error: expected function, got: {}", self.printer.print(&value, true));
-                }
-                Err(Error::TypeError) => {
-                    println!("type error");
-                }
-                Err(Error::UnknownLexerError) => {
-                    println!("lexer error: unknown");
+                Err(Error::Exit(code)) => process::exit(code),
+                Err(e) => {
+                    self.report(&e);
                 }
             }
         }
     }
 
+    /// Prints `error` to standard error, so that it doesn't mix with the
+    /// output of commands, as a JSON object with `--json-errors`.
+    fn report(&self, error: &Error) {
+        error.report(self.json_errors);
+    }
+
+    /// Adds the entries that other sessions saved since the last prompt.
+    fn merge_history(&mut self) -> Result<()> {
+        if let Some(ref history) = self.history {
+            for entry in history.load_new()? {
+                self.readliner.add_history_entry(&entry);
+            }
+        }
+
+        Ok(())
+    }
+
     fn read_eval(&mut self) -> Result<Option<LispValue>> {
-        let line = self.readliner.readline()?;
+        if let Err(e) = self.merge_history() {
+            self.report(&e);
+        }
+
+        let mut line = self.readliner.readline()?;
+
+        // here-documents continue on the following lines up to their delimiter
+        while !heredoc::is_complete(&line) {
+            line.push('\n');
+            line.push_str(&self.readliner.readline()?);
+        }
+
+        let result = self.engine.run(&line);
+
+        // a history that can't be written shouldn't stop commands from running
+        if let Err(e) = self.record_history(&line) {
+            self.report(&e);
+        }
+
+        result
+    }
+
+    fn record_history(&mut self, line: &str) -> Result<()> {
+        if history::is_ignored(line, &self.engine.env())? {
+            return Ok(());
+        }
+
+        let entry = Entry::new(line);
+
+        let appended = match self.history {
+            Some(ref history) => history.append(&entry),
+            None => Ok(vec![]),
+        };
+
+        // entries other sessions saved while the command ran come first, as in the file
+        for other in appended.as_ref().map(|entries| entries.as_slice()).unwrap_or(&[]) {
+            self.readliner.add_history_entry(other);
+        }
 
-        self.readliner.add_history_entry(&line);
+        self.readliner.add_history_entry(&entry);
 
-        self.engine.run(&line)
+        appended.map(|_| ())
     }
 }
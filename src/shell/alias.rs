@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use error::{Error, Result};
+use env::{Env, env_get, env_set};
+use types::{self, HashKey, LispType, LispValue};
+
+use shell::lexer::tokenize;
+use shell::token::Token;
+use shell::word;
+
+/// Value of alias `name` in the `ALIASES` hash: a string to be expanded or a
+/// function to be called with the arguments.
+pub fn lookup(env: &Env, name: &str) -> Result<Option<LispValue>> {
+    Ok(aliases(env)?.remove(&HashKey::from(name)))
+}
+
+pub fn all(env: &Env) -> Result<Vec<(String, LispValue)>> {
+    let mut aliases: Vec<_> = aliases(env)?.into_iter().filter_map(|(key, value)| {
+        match key {
+            HashKey::Strn(name) => Some((name, value)),
+            _ => None,
+        }
+    }).collect();
+
+    aliases.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(aliases)
+}
+
+pub fn set(env: &Env, name: &str, value: LispValue) -> Result<()> {
+    let mut data = aliases(env)?;
+
+    data.insert(HashKey::from(name), value);
+    env_set(env, "ALIASES", types::hash_map(data));
+
+    Ok(())
+}
+
+pub fn remove(env: &Env, name: &str) -> Result<()> {
+    let mut data = aliases(env)?;
+
+    match data.remove(&HashKey::from(name)) {
+        Some(_) => {
+            env_set(env, "ALIASES", types::hash_map(data));
+            Ok(())
+        },
+        None => Err(Error::AliasNotFound(name.to_owned())),
+    }
+}
+
+pub fn clear(env: &Env) {
+    env_set(env, "ALIASES", types::hash_map(HashMap::new()));
+}
+
+fn aliases(env: &Env) -> Result<HashMap<HashKey, LispValue>> {
+    match env_get(env, "ALIASES").ok() {
+        Some(value) => {
+            match *value {
+                LispType::HashMap(ref data) => Ok(data.clone()),
+                _ => Err(Error::TypeError),
+            }
+        },
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Expands string aliases in every command position of `tokens`.
+///
+/// The replacement is expanded again, except for aliases that are already
+/// being expanded, so `ls='ls -G'` and cycles like `a=b`, `b=a` terminate.
+pub fn expand(tokens: Vec<Token>, env: &Env) -> Result<Vec<Token>> {
+    expand_tokens(tokens, env, &mut vec![])
+}
+
+fn expand_tokens(tokens: Vec<Token>, env: &Env, expanding: &mut Vec<String>) -> Result<Vec<Token>> {
+    let mut result = vec![];
+    let mut command_position = true;
+
+    for token in tokens {
+        match token {
+            Token::Ident(name) => {
+                if command_position && word::split_assignment(&name).is_some() {
+                    result.push(Token::Ident(name));
+                    continue;
+                }
+
+                if command_position {
+                    expand_command(name, env, expanding, &mut result)?;
+                } else {
+                    result.push(Token::Ident(name));
+                }
+                command_position = false;
+            },
+            Token::Semicolon | Token::And | Token::Pipe => {
+                result.push(token);
+                command_position = true;
+            },
+            _ => {
+                result.push(token);
+                command_position = false;
+            },
+        }
+    }
+
+    Ok(result)
+}
+
+fn expand_command(name: String, env: &Env, expanding: &mut Vec<String>, result: &mut Vec<Token>) -> Result<()> {
+    if expanding.contains(&name) {
+        result.push(Token::Ident(name));
+        return Ok(());
+    }
+
+    let value = match lookup(env, &name)? {
+        Some(value) => value,
+        None => {
+            result.push(Token::Ident(name));
+            return Ok(());
+        },
+    };
+
+    match *value {
+        LispType::Strn(ref replacement) => {
+            let tokens = tokenize(replacement)?;
+
+            expanding.push(name);
+            let expanded = expand_tokens(tokens, env, expanding);
+            expanding.pop();
+
+            result.extend(expanded?);
+        },
+        LispType::Function(_) | LispType::NativeFunction(_) => {
+            result.push(Token::Ident(name));
+        },
+        _ => return Err(Error::TypeError),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use env::env_new;
+    use super::*;
+
+    fn env_with_aliases(aliases: &[(&str, &str)]) -> Env {
+        let env = env_new(None);
+
+        for &(name, value) in aliases {
+            set(&env, name, types::string(value.to_owned())).unwrap();
+        }
+
+        env
+    }
+
+    fn expand_str(input: &str, env: &Env) -> Vec<Token> {
+        expand(tokenize(input).unwrap(), env).unwrap()
+    }
+
+    #[test]
+    fn expand_without_aliases() {
+        let env = env_new(None);
+
+        assert_eq!(expand_str("ls -la", &env), tokenize("ls -la").unwrap());
+    }
+
+    #[test]
+    fn expand_only_command_words() {
+        let env = env_with_aliases(&[("ll", "ls -la")]);
+
+        assert_eq!(expand_str("ll ll", &env), tokenize("ls -la ll").unwrap());
+    }
+
+    #[test]
+    fn expand_recursively() {
+        let env = env_with_aliases(&[("ll", "ls -la"), ("l", "ll -h")]);
+
+        assert_eq!(expand_str("l dir", &env), tokenize("ls -la -h dir").unwrap());
+    }
+
+    #[test]
+    fn expand_self_reference_once() {
+        let env = env_with_aliases(&[("ls", "ls -G")]);
+
+        assert_eq!(expand_str("ls", &env), tokenize("ls -G").unwrap());
+    }
+
+    #[test]
+    fn expand_cycle() {
+        let env = env_with_aliases(&[("a", "b 1"), ("b", "a 2")]);
+
+        assert_eq!(expand_str("a", &env), tokenize("a 2 1").unwrap());
+    }
+
+    #[test]
+    fn expand_after_separators() {
+        let env = env_with_aliases(&[("g", "git")]);
+
+        assert_eq!(expand_str("g a; g b && g c | g d", &env),
+                   tokenize("git a; git b && git c | git d").unwrap());
+    }
+
+    #[test]
+    fn expand_after_assignments() {
+        let env = env_with_aliases(&[("g", "git")]);
+
+        assert_eq!(expand_str("A=1 g", &env), tokenize("A=1 git").unwrap());
+    }
+
+    #[test]
+    fn expand_skips_redirection_targets() {
+        let env = env_with_aliases(&[("g", "git")]);
+
+        assert_eq!(expand_str("echo > g", &env), tokenize("echo > g").unwrap());
+    }
+
+    #[test]
+    fn remove_alias() {
+        let env = env_with_aliases(&[("g", "git")]);
+
+        remove(&env, "g").unwrap();
+
+        assert_eq!(lookup(&env, "g").unwrap(), None);
+        assert!(remove(&env, "g").is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use shell::word;
+
+/// Most words a brace expansion may produce.
+const MAX_WORDS: usize = 1 << 20;
+
+/// Deepest nesting of braces that are expanded.
+const MAX_DEPTH: usize = 32;
+
+/// Expands the braces in a shell word into several words.
+///
+/// `{a,b}` expands to each of its comma separated alternatives, and `{1..10}`,
+/// `{01..10..3}` or `{a..e}` to a sequence, with the text around the braces
+/// added to every result. Braces may nest. Quoted or escaped braces, braces of
+/// expansions like `${NAME}` and braces that are neither a list nor a sequence
+/// are left alone.
+///
+/// The results are still unexpanded words, so this happens before variables,
+/// substitutions or any other part of a word are expanded. A word that would
+/// expand to more than `MAX_WORDS` words is left alone too, instead of
+/// running out of memory, and so are groups nested more than `MAX_DEPTH` deep.
+pub fn expand(word: &str) -> Vec<String> {
+    if nesting(word) > MAX_DEPTH {
+        return vec![word.to_owned()];
+    }
+
+    let mut words = vec![String::new()];
+    let mut start = 0;
+    let mut search = 0;
+
+    while let Some(open) = find_open(word, search) {
+        if let Some((length, alternatives)) = parse_group(&word[open..]) {
+            if words.len().saturating_mul(alternatives.len()) > MAX_WORDS {
+                return vec![word.to_owned()];
+            }
+
+            let preamble = &word[start..open];
+
+            words = words.iter()
+                .flat_map(|prefix| alternatives.iter().map(move |alternative| format!("{}{}{}", prefix, preamble, alternative)))
+                .collect();
+
+            start = open + length;
+            search = start;
+        } else {
+            search = open + 1;
+        }
+    }
+
+    for expanded in &mut words {
+        expanded.push_str(&word[start..]);
+    }
+
+    words
+}
+
+/// How deeply the unquoted braces of `word` nest.
+fn nesting(word: &str) -> usize {
+    let mut index = 0;
+    let mut depth: usize = 0;
+    let mut deepest = 0;
+
+    while index < word.len() {
+        if word[index..].starts_with('{') {
+            depth += 1;
+            deepest = deepest.max(depth);
+        } else if word[index..].starts_with('}') {
+            depth = depth.saturating_sub(1);
+        }
+
+        index += next_part(&word[index..]);
+    }
+
+    deepest
+}
+
+/// Index of the first unquoted `{` in `word`, from `start`.
+fn find_open(word: &str, start: usize) -> Option<usize> {
+    let mut index = start;
+
+    while index < word.len() {
+        if word[index..].starts_with('{') {
+            return Some(index);
+        }
+
+        index += next_part(&word[index..]);
+    }
+
+    None
+}
+
+/// Byte length and expanded alternatives of the brace group at the start of
+/// `input`, if it is a list or a sequence.
+fn parse_group(input: &str) -> Option<(usize, Vec<String>)> {
+    let mut index = 1;
+    let mut depth = 0;
+    let mut commas = vec![];
+
+    while index < input.len() {
+        match input[index..].chars().next() {
+            Some('{') => depth += 1,
+            Some('}') if depth > 0 => depth -= 1,
+            Some('}') => {
+                let alternatives = if commas.is_empty() {
+                    sequence(&input[1..index])?
+                } else {
+                    list(input, &commas, index)
+                };
+
+                return Some((index + 1, alternatives));
+            },
+            Some(',') if depth == 0 => commas.push(index),
+            _ => {},
+        }
+
+        index += next_part(&input[index..]);
+    }
+
+    None
+}
+
+fn next_part(input: &str) -> usize {
+    match word::part_length(input) {
+        Some(length) => length,
+        None => input.chars().next().map_or(1, char::len_utf8),
+    }
+}
+
+/// The alternatives between the braces of `input`, separated at `commas`.
+fn list(input: &str, commas: &[usize], close: usize) -> Vec<String> {
+    let mut alternatives = vec![];
+    let mut start = 1;
+
+    for &end in commas.iter().chain(Some(&close)) {
+        alternatives.extend(expand(&input[start..end]));
+        start = end + 1;
+    }
+
+    alternatives
+}
+
+/// `first..last` or `first..last..step`, between numbers or single letters.
+///
+/// Numbers are padded with zeros to the same width when either end starts with
+/// a zero.
+fn sequence(body: &str) -> Option<Vec<String>> {
+    let parts: Vec<&str> = body.split("..").collect();
+
+    let step = match parts.len() {
+        2 => 1,
+        3 => parts[2].parse::<i64>().ok()?.checked_abs()?.max(1),
+        _ => return None,
+    };
+
+    if let (Ok(first), Ok(last)) = (parts[0].parse::<i64>(), parts[1].parse::<i64>()) {
+        if (i128::from(last) - i128::from(first)).abs() / i128::from(step) >= MAX_WORDS as i128 {
+            return None;
+        }
+
+        let width = if is_padded(parts[0]) || is_padded(parts[1]) {
+            parts[0].len().max(parts[1].len())
+        } else {
+            0
+        };
+
+        return Some(range(first, last, step).into_iter()
+            .map(|n| format!("{:01$}", n, width))
+            .collect());
+    }
+
+    let first = letter(parts[0])?;
+    let last = letter(parts[1])?;
+
+    Some(range(first as i64, last as i64, step).into_iter()
+        .filter_map(|n| ::std::char::from_u32(n as u32))
+        .map(escape)
+        .collect())
+}
+
+fn is_padded(number: &str) -> bool {
+    let digits = number.trim_start_matches(&['-', '+'][..]);
+
+    digits.len() > 1 && digits.starts_with('0')
+}
+
+fn letter(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() => Some(c),
+        _ => None,
+    }
+}
+
+/// A sequence like `{Z..a}` also goes through characters that are special to
+/// the shell, so the ones that aren't letters are escaped.
+fn escape(c: char) -> String {
+    if c.is_ascii_alphabetic() {
+        c.to_string()
+    } else {
+        format!("\\{}", c)
+    }
+}
+
+/// The values from `first` to `last`, going up or down by `step`.
+fn range(first: i64, last: i64, step: i64) -> Vec<i64> {
+    let mut values = vec![];
+    let mut value = Some(first);
+
+    while let Some(current) = value {
+        if (first <= last && current > last) || (first > last && current < last) {
+            break;
+        }
+
+        values.push(current);
+
+        value = if first <= last {
+            current.checked_add(step)
+        } else {
+            current.checked_sub(step)
+        };
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_lists() {
+        assert_eq!(expand("src/{lisp,shell,core}"), vec!["src/lisp", "src/shell", "src/core"]);
+        assert_eq!(expand("a{b,c}d{1,2}"), vec!["abd1", "abd2", "acd1", "acd2"]);
+        assert_eq!(expand("file{,.bak}"), vec!["file", "file.bak"]);
+    }
+
+    #[test]
+    fn expand_nested_lists() {
+        assert_eq!(expand("{a,b{1,2},c}"), vec!["a", "b1", "b2", "c"]);
+        assert_eq!(expand("{x,y}{{1..2},z}"), vec!["x1", "x2", "xz", "y1", "y2", "yz"]);
+    }
+
+    #[test]
+    fn expand_numeric_sequences() {
+        assert_eq!(expand("file{1..3}.txt"), vec!["file1.txt", "file2.txt", "file3.txt"]);
+        assert_eq!(expand("{3..1}"), vec!["3", "2", "1"]);
+        assert_eq!(expand("{-1..1}"), vec!["-1", "0", "1"]);
+        assert_eq!(expand("{0..10..5}"), vec!["0", "5", "10"]);
+        assert_eq!(expand("{10..0..-4}"), vec!["10", "6", "2"]);
+    }
+
+    #[test]
+    fn expand_padded_sequences() {
+        assert_eq!(expand("{08..10}"), vec!["08", "09", "10"]);
+        assert_eq!(expand("{1..003}"), vec!["001", "002", "003"]);
+        assert_eq!(expand("{-05..-03}"), vec!["-05", "-04", "-03"]);
+    }
+
+    #[test]
+    fn expand_char_sequences() {
+        assert_eq!(expand("{a..e..2}"), vec!["a", "c", "e"]);
+        assert_eq!(expand("{C..A}"), vec!["C", "B", "A"]);
+        assert_eq!(expand("{Z..a}")[1], "\\[");
+    }
+
+    #[test]
+    fn keep_other_braces() {
+        assert_eq!(expand("{a}"), vec!["{a}"]);
+        assert_eq!(expand("{}"), vec!["{}"]);
+        assert_eq!(expand("{1..a}"), vec!["{1..a}"]);
+        assert_eq!(expand("{a,b"), vec!["{a,b"]);
+        assert_eq!(expand("{a}{b,c}"), vec!["{a}b", "{a}c"]);
+    }
+
+    #[test]
+    fn keep_huge_sequences() {
+        assert_eq!(expand("{1..9223372036854775807}"), vec!["{1..9223372036854775807}"]);
+        assert_eq!(expand("{0..99999999999}"), vec!["{0..99999999999}"]);
+        assert_eq!(expand("a{1..1048577}"), vec!["a{1..1048577}"]);
+        assert_eq!(expand("{1..1048576..2}").len(), 524288);
+        assert_eq!(expand("{1..1024}{1..1024}{1,2}"), vec!["{1..1024}{1..1024}{1,2}"]);
+
+        let many = "{a,b}".repeat(100_000);
+        assert_eq!(expand(&many), vec![many.clone()]);
+    }
+
+    #[test]
+    fn keep_deeply_nested_braces() {
+        let nested = format!("{}{}", "{a,".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+        assert_eq!(expand(&nested).len(), MAX_DEPTH + 1);
+
+        let too_deep = format!("{}{}", "{a,".repeat(100_000), "}".repeat(100_000));
+        assert_eq!(expand(&too_deep), vec![too_deep.clone()]);
+        assert_eq!(expand(&"{".repeat(100_000)).len(), 1);
+    }
+
+    #[test]
+    fn keep_quoted_braces() {
+        assert_eq!(expand("'{a,b}'"), vec!["'{a,b}'"]);
+        assert_eq!(expand("\\{a,b}"), vec!["\\{a,b}"]);
+        assert_eq!(expand("${HOME}"), vec!["${HOME}"]);
+        assert_eq!(expand("{\"a,b\",c}"), vec!["\"a,b\"", "c"]);
+        assert_eq!(expand("{$(echo a,b),c}"), vec!["$(echo a,b)", "c"]);
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use env::Env;
+use error::{Error, Result};
+use printer::Printer;
+use types::{self, LispType, LispValue};
+use shell::alias;
+
+pub fn run(args: Vec<String>, env: &Env, out: &mut dyn Write) -> Result<()> {
+    if args.is_empty() {
+        for (name, value) in alias::all(env)? {
+            print_alias(&name, &value, out)?;
+        }
+
+        return Ok(());
+    }
+
+    for arg in args {
+        match arg.find('=') {
+            Some(index) => {
+                let (name, value) = (&arg[..index], &arg[index + 1..]);
+
+                alias::set(env, name, types::string(value.to_owned()))?;
+            },
+            None => {
+                match alias::lookup(env, &arg)? {
+                    Some(value) => print_alias(&arg, &value, out)?,
+                    None => return Err(Error::AliasNotFound(arg)),
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn print_alias(name: &str, value: &LispValue, out: &mut dyn Write) -> Result<()> {
+    match **value {
+        LispType::Strn(ref value) => writeln!(out, "alias {}='{}'", name, value)?,
+        _ => writeln!(out, "alias {}={}", name, Printer::new().print(value, true))?,
+    }
+
+    Ok(())
+}
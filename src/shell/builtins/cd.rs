@@ -1,16 +1,95 @@
 use std::env;
-use std::path::Path;
+use std::env::home_dir;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use error::Result;
+use error::{Error, Result};
+use frecency::{self, Database};
 
-pub fn run(args: Vec<String>) -> Result<()> {
-    let target = if args.is_empty() {
-        env::home_dir().expect("Could not get your home dir")
-    } else {
-        Path::new(&args[0]).to_path_buf()
+pub fn run(args: Vec<String>, out: &mut dyn Write) -> Result<()> {
+    let target = match args.first().map(|arg| arg.as_str()) {
+        None => home()?,
+        Some("-") => {
+            let target = match env::var_os("OLDPWD") {
+                Some(dir) => PathBuf::from(dir),
+                None => return Err(Error::BuiltinError("cd".to_owned(), "OLDPWD not set".to_owned())),
+            };
+            writeln!(out, "{}", target.display())?;
+            target
+        },
+        Some(dir) => {
+            match search_cdpath(dir, env::var_os("CDPATH")) {
+                Some(target) => {
+                    writeln!(out, "{}", target.display())?;
+                    target
+                },
+                None => PathBuf::from(dir),
+            }
+        },
     };
 
-    env::set_current_dir(&target)?;
+    change_directory(&target)
+}
+
+/// Changes the current directory, keeping `$PWD` and `$OLDPWD` up to date,
+/// and records the new directory in the frecency database used by `z`.
+pub fn change_directory(target: &Path) -> Result<()> {
+    let previous = env::current_dir().ok();
+
+    env::set_current_dir(target)?;
+
+    if let Some(previous) = previous {
+        env::set_var("OLDPWD", previous);
+    }
+
+    let current = env::current_dir()?;
+    env::set_var("PWD", &current);
+
+    // failing to record the visit shouldn't fail the directory change
+    let _ = Database::new().visit(&current, frecency::now());
 
     Ok(())
 }
+
+fn home() -> Result<PathBuf> {
+    match env::var_os("HOME") {
+        Some(ref dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => home_dir().ok_or_else(|| Error::BuiltinError("cd".to_owned(), "HOME not set".to_owned())),
+    }
+}
+
+/// Looks `dir` up in the directories of `CDPATH`, unless it is absolute or
+/// explicitly relative to the current directory.
+fn search_cdpath(dir: &str, cdpath: Option<::std::ffi::OsString>) -> Option<PathBuf> {
+    if dir.starts_with('/') || dir == "." || dir == ".." || dir.starts_with("./") || dir.starts_with("../") {
+        return None;
+    }
+
+    let cdpath = cdpath?;
+
+    env::split_paths(&cdpath)
+        .filter(|base| !base.as_os_str().is_empty())
+        .map(|base| base.join(dir))
+        .find(|candidate| candidate.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process;
+    use super::*;
+
+    #[test]
+    fn search_cdpath_finds_first_match() {
+        let mut base = env::temp_dir();
+        base.push(format!("lish-cdpath-{}", process::id()));
+        fs::create_dir_all(base.join("b").join("project")).unwrap();
+
+        let cdpath = env::join_paths(vec![base.join("a"), base.join("b")]).unwrap();
+
+        assert_eq!(search_cdpath("project", Some(cdpath.clone())), Some(base.join("b").join("project")));
+        assert_eq!(search_cdpath("missing", Some(cdpath.clone())), None);
+        assert_eq!(search_cdpath("./project", Some(cdpath)), None);
+        assert_eq!(search_cdpath("project", None), None);
+    }
+}
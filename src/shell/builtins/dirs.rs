@@ -0,0 +1,22 @@
+use std::io::Write;
+
+use error::Result;
+use shell::state::State;
+
+pub fn run(args: Vec<String>, state: &mut State, out: &mut dyn Write) -> Result<()> {
+    if args.first().map(|arg| arg.as_str()) == Some("-c") {
+        state.dir_stack.borrow_mut().clear();
+
+        return Ok(());
+    }
+
+    print(state, out)
+}
+
+pub fn print(state: &State, out: &mut dyn Write) -> Result<()> {
+    let dirs: Vec<String> = state.dirs().iter().map(|dir| dir.to_string_lossy().into_owned()).collect();
+
+    writeln!(out, "{}", dirs.join(" "))?;
+
+    Ok(())
+}
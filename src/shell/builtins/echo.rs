@@ -1,7 +1,9 @@
+use std::io::Write;
+
 use error::Result;
 
-pub fn run(args: Vec<String>) -> Result<()> {
-    println!("{}", args.join(" "));
+pub fn run(args: Vec<String>, out: &mut dyn Write) -> Result<()> {
+    writeln!(out, "{}", args.join(" "))?;
 
     Ok(())
 }
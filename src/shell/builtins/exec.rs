@@ -0,0 +1,20 @@
+use std::io::ErrorKind;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+use error::{Error, Result};
+
+/// Replaces the shell process with the given command.
+pub fn run(args: Vec<String>) -> Result<()> {
+    let (cmd, args) = match args.split_first() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+
+    let err = Command::new(cmd).args(args).exec();
+
+    match err.kind() {
+        ErrorKind::NotFound => Err(Error::CommandNotFound(cmd.to_owned())),
+        _ => Err(Error::IoError(err)),
+    }
+}
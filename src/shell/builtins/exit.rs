@@ -0,0 +1,12 @@
+use error::{Error, Result};
+
+pub fn run(args: Vec<String>) -> Result<()> {
+    let code = match args.first() {
+        Some(code) => code.parse().map_err(|_| {
+            Error::BuiltinError("exit".to_owned(), format!("{}: numeric argument required", code))
+        })?,
+        None => 0,
+    };
+
+    Err(Error::Exit(code))
+}
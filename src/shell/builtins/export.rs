@@ -0,0 +1,48 @@
+use std::env;
+use std::io::Write;
+
+use error::{Error, Result};
+use shell::state::State;
+use shell::word::is_name;
+
+/// Sets `NAME=value` pairs in the environment of the shell and its children,
+/// or moves shell variables given by `NAME` there. Lists the environment when
+/// called without arguments.
+pub fn run(args: Vec<String>, state: &mut State, out: &mut dyn Write) -> Result<()> {
+    if args.is_empty() {
+        let mut vars: Vec<(String, String)> = env::vars().collect();
+        vars.sort();
+
+        for (name, value) in vars {
+            writeln!(out, "export {}='{}'", name, value.replace('\'', "'\\''"))?;
+        }
+
+        return Ok(());
+    }
+
+    for arg in args {
+        match arg.find('=') {
+            Some(index) => {
+                let (name, value) = (&arg[..index], &arg[index + 1..]);
+
+                if !is_name(name) {
+                    return Err(Error::BuiltinError("export".to_owned(), format!("{}: not a valid identifier", name)));
+                }
+
+                state.vars.remove(name);
+                env::set_var(name, value);
+            },
+            None => {
+                if !is_name(&arg) {
+                    return Err(Error::BuiltinError("export".to_owned(), format!("{}: not a valid identifier", arg)));
+                }
+
+                if let Some(value) = state.vars.remove(&arg) {
+                    env::set_var(arg, value);
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use std::io::Write;
+
+use history::History;
+use error::{Error, Result};
+
+pub fn run(args: Vec<String>, out: &mut dyn Write) -> Result<()> {
+    let entries = History::new().load()?;
+
+    let start = match args.first() {
+        Some(count) => {
+            let count: usize = count.parse().map_err(|_| Error::TypeError)?;
+            entries.len().saturating_sub(count)
+        },
+        None => 0,
+    };
+
+    for (index, entry) in entries.iter().enumerate().skip(start) {
+        writeln!(out, "{:>5}  {}", index + 1, entry.command)?;
+    }
+
+    Ok(())
+}
@@ -1,2 +1,58 @@
+use std::io::Write;
+
+use env::Env;
+use error::Result;
+use shell::state::State;
+
 pub mod echo;
 pub mod cd;
+pub mod history;
+pub mod alias;
+pub mod unalias;
+pub mod pushd;
+pub mod popd;
+pub mod dirs;
+pub mod z;
+pub mod exit;
+pub mod export;
+pub mod unset;
+pub mod source;
+pub mod exec;
+pub mod type_;
+pub mod which;
+pub mod pwd;
+
+/// What a builtin can reach besides its arguments and output.
+pub struct Context<'a> {
+    pub env: &'a Env,
+    pub state: &'a mut State,
+}
+
+pub type Builtin = fn(Vec<String>, &mut Context, &mut dyn Write) -> Result<()>;
+
+const BUILTINS: &[(&str, Builtin)] = &[
+    ("echo", |args, _, out| echo::run(args, out)),
+    ("cd", |args, _, out| cd::run(args, out)),
+    ("history", |args, _, out| history::run(args, out)),
+    ("alias", |args, context, out| alias::run(args, context.env, out)),
+    ("unalias", |args, context, _| unalias::run(args, context.env)),
+    ("pushd", |args, context, out| pushd::run(args, context.state, out)),
+    ("popd", |_, context, out| popd::run(context.state, out)),
+    ("dirs", |args, context, out| dirs::run(args, context.state, out)),
+    ("z", |args, _, out| z::run(args, out)),
+    ("exit", |args, _, _| exit::run(args)),
+    ("export", |args, context, out| export::run(args, context.state, out)),
+    ("unset", |args, context, _| unset::run(args, context.state)),
+    ("source", |args, context, _| source::run(args, context.env, context.state)),
+    ("exec", |args, _, _| exec::run(args)),
+    ("type", |args, context, out| type_::run(args, context.env, out)),
+    ("which", |args, context, out| which::run(args, context.env, out)),
+    ("pwd", |_, _, out| pwd::run(out)),
+    // `command NAME` is run by the evaluator, which skips aliases and lisp
+    // functions named NAME; on its own it does nothing
+    ("command", |_, _, _| Ok(())),
+];
+
+pub fn lookup(name: &str) -> Option<Builtin> {
+    BUILTINS.iter().find(|&&(builtin, _)| builtin == name).map(|&(_, run)| run)
+}
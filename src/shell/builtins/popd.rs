@@ -0,0 +1,18 @@
+use std::io::Write;
+
+use error::{Error, Result};
+use shell::state::State;
+use shell::builtins::cd::change_directory;
+use shell::builtins::dirs;
+
+pub fn run(state: &mut State, out: &mut dyn Write) -> Result<()> {
+    let target = match state.dir_stack.borrow().last() {
+        Some(dir) => dir.clone(),
+        None => return Err(Error::BuiltinError("popd".to_owned(), "directory stack empty".to_owned())),
+    };
+
+    change_directory(&target)?;
+    state.dir_stack.borrow_mut().pop();
+
+    dirs::print(state, out)
+}
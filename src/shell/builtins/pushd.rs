@@ -0,0 +1,33 @@
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+
+use error::{Error, Result};
+use shell::state::State;
+use shell::builtins::cd::change_directory;
+use shell::builtins::dirs;
+
+pub fn run(args: Vec<String>, state: &mut State, out: &mut dyn Write) -> Result<()> {
+    let current = env::current_dir()?;
+
+    let target = match args.first() {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            match state.dir_stack.borrow_mut().pop() {
+                Some(dir) => dir,
+                None => return Err(Error::BuiltinError("pushd".to_owned(), "no other directory".to_owned())),
+            }
+        },
+    };
+
+    if let Err(e) = change_directory(&target) {
+        if args.is_empty() {
+            state.dir_stack.borrow_mut().push(target);
+        }
+        return Err(e);
+    }
+
+    state.dir_stack.borrow_mut().push(current);
+
+    dirs::print(state, out)
+}
@@ -0,0 +1,10 @@
+use std::env;
+use std::io::Write;
+
+use error::Result;
+
+pub fn run(out: &mut dyn Write) -> Result<()> {
+    writeln!(out, "{}", env::current_dir()?.display())?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use std::fs::File;
+use std::io::Read;
+
+use env::Env;
+use error::{Error, Result};
+use shell::engine;
+use shell::state::State;
+
+/// Runs the shell lines and lisp forms of a file in the current session.
+pub fn run(args: Vec<String>, env: &Env, state: &mut State) -> Result<()> {
+    let path = match args.first() {
+        Some(path) => path,
+        None => return Err(Error::BuiltinError("source".to_owned(), "filename argument required".to_owned())),
+    };
+
+    let mut script = String::new();
+    File::open(path).and_then(|mut f| f.read_to_string(&mut script))?;
+
+    engine::run_script(&script, env, state)
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use env::Env;
+use error::{Error, Result};
+use shell::evaluator::{command_kind, CommandKind};
+use types::LispType;
+
+/// How `describe` words what a name refers to.
+pub enum Style {
+    /// `ls is /bin/ls`, for `type`.
+    Type,
+    /// `/bin/ls`, for `which`.
+    Which,
+}
+
+impl Style {
+    fn builtin(&self) -> &'static str {
+        match *self {
+            Style::Type => "type",
+            Style::Which => "which",
+        }
+    }
+
+    /// The line printed for `name`, which is run as `kind`.
+    fn line(&self, name: &str, kind: &CommandKind) -> String {
+        match *self {
+            Style::Type => match *kind {
+                CommandKind::Alias(ref value) => match **value {
+                    LispType::Strn(ref replacement) => format!("{} is aliased to `{}'", name, replacement),
+                    _ => format!("{} is aliased to a lisp function", name),
+                },
+                CommandKind::Builtin => format!("{} is a shell builtin", name),
+                CommandKind::Function => format!("{} is a lisp function", name),
+                CommandKind::File(ref path) => format!("{} is {}", name, path.display()),
+            },
+            Style::Which => match *kind {
+                CommandKind::Alias(ref value) => match **value {
+                    LispType::Strn(ref replacement) => format!("{}: aliased to {}", name, replacement),
+                    _ => format!("{}: aliased to a lisp function", name),
+                },
+                CommandKind::Builtin => format!("{}: shell built-in command", name),
+                CommandKind::Function => format!("{}: lisp function", name),
+                CommandKind::File(ref path) => path.display().to_string(),
+            },
+        }
+    }
+}
+
+/// Describes how each name would be run as a command.
+pub fn run(args: Vec<String>, env: &Env, out: &mut dyn Write) -> Result<()> {
+    describe(Style::Type, args, env, out)
+}
+
+/// Prints what each name refers to as a command, failing at the first one
+/// that isn't found.
+pub fn describe(style: Style, args: Vec<String>, env: &Env, out: &mut dyn Write) -> Result<()> {
+    for name in args {
+        match command_kind(&name, env)? {
+            Some(kind) => writeln!(out, "{}", style.line(&name, &kind))?,
+            None => return Err(Error::BuiltinError(style.builtin().to_owned(), format!("{}: not found", name))),
+        }
+    }
+
+    Ok(())
+}
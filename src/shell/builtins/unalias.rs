@@ -0,0 +1,17 @@
+use env::Env;
+use error::Result;
+use shell::alias;
+
+pub fn run(args: Vec<String>, env: &Env) -> Result<()> {
+    if args.first().map(|arg| arg.as_str()) == Some("-a") {
+        alias::clear(env);
+
+        return Ok(());
+    }
+
+    for name in args {
+        alias::remove(env, &name)?;
+    }
+
+    Ok(())
+}
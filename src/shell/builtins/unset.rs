@@ -0,0 +1,19 @@
+use std::env;
+
+use error::{Error, Result};
+use shell::state::State;
+use shell::word::is_name;
+
+/// Removes shell and environment variables.
+pub fn run(args: Vec<String>, state: &mut State) -> Result<()> {
+    for name in args {
+        if !is_name(&name) {
+            return Err(Error::BuiltinError("unset".to_owned(), format!("{}: not a valid identifier", name)));
+        }
+
+        state.vars.remove(&name);
+        env::remove_var(name);
+    }
+
+    Ok(())
+}
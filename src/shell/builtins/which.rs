@@ -0,0 +1,10 @@
+use std::io::Write;
+
+use env::Env;
+use error::Result;
+use shell::builtins::type_::{describe, Style};
+
+/// Prints the path of each program, or what else the name refers to.
+pub fn run(args: Vec<String>, env: &Env, out: &mut dyn Write) -> Result<()> {
+    describe(Style::Which, args, env, out)
+}
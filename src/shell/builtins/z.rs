@@ -0,0 +1,25 @@
+use std::io::Write;
+use std::path::Path;
+
+use error::{Error, Result};
+use frecency::{self, Database};
+use shell::builtins::cd::change_directory;
+
+/// Jumps to the most frecent directory matching every keyword, or lists the
+/// known directories with their scores when called without arguments.
+pub fn run(args: Vec<String>, out: &mut dyn Write) -> Result<()> {
+    let now = frecency::now();
+    let dirs = Database::new().rank(&args, now)?;
+
+    if args.is_empty() {
+        for dir in dirs.iter().rev() {
+            writeln!(out, "{:<10.1} {}", dir.frecency(now), dir.path)?;
+        }
+        return Ok(());
+    }
+
+    match dirs.first() {
+        Some(dir) => change_directory(Path::new(&dir.path)),
+        None => Err(Error::BuiltinError("z".to_owned(), "no match found".to_owned())),
+    }
+}
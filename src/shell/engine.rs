@@ -1,89 +1,69 @@
-use error::{Error, Result};
-use env::{Env, env_get};
-use types::LispType;
+use error::Result;
+use env::Env;
+use types::ShellList;
 
+use engine;
+use shell::alias;
 use shell::parser::Parser;
-use shell::token::Token;
-use shell::lexer::tokenize;
-use shell::evaluator::Evaluator;
+use shell::heredoc;
+use shell::evaluator::{self, Evaluator};
+use shell::script;
+use shell::state::State;
 
 pub struct Engine {
-    parser: Parser,
-    evaluator: Evaluator,
+    state: State,
 }
 
 impl Engine {
     pub fn new() -> Engine {
         Engine {
-            parser: Parser::new(),
-            evaluator: Evaluator::new(),
+            state: State::new(),
         }
     }
 
-    pub fn run(&mut self, input: &str, env: Env) -> Result<()> {
-        let tokens = tokenize(input)?;
-
-        let tokens = resolve_alias(tokens, env)?;
-
-        self.parser.add_tokens(tokens);
-
-        let expr = self.parser.parse()?;
+    /// Gives lisp access to the shell state through natives like `dir-stack`.
+    pub fn bind(&self, env: &Env) {
+        self.state.bind(env);
+    }
 
-        self.evaluator.eval(expr)
+    pub fn state(&mut self) -> &mut State {
+        &mut self.state
     }
 }
 
-fn resolve_alias(tokens: Vec<Token>, env: Env) -> Result<Vec<Token>> {
-    if tokens.len() < 1 {
-        return Ok(tokens);
-    }
+/// Runs a line of shell commands.
+pub fn run_line(input: &str, env: Env, state: &mut State) -> Result<()> {
+    let expr = parse(input, &env)?;
 
-    let mapping = get_alias_mapping(&tokens[0], &env)?;
+    Evaluator::new().eval(expr, env, state)
+}
 
-    match mapping {
-        None => Ok(tokens),
-        Some(value) => {
-            let mut words = tokenize(&value)?;
+/// Tokenizes `input`, expands its aliases and parses it.
+pub fn parse(input: &str, env: &Env) -> Result<ShellList> {
+    let tokens = heredoc::tokenize_lines(input)?;
 
-            if let Some((_, args)) = tokens.split_first() {
-                words.extend_from_slice(args);
+    let tokens = alias::expand(tokens, env)?;
 
-                Ok(words)
-            } else {
-                panic!("impossible");
-            }
-        }
-    }
+    let mut parser = Parser::new();
+    parser.add_tokens(tokens);
+
+    parser.parse()
 }
 
-fn get_alias_mapping(token: &Token, env: &Env) -> Result<Option<String>> {
-    let name = match token {
-        &Token::Ident(ref data) => data,
-    };
+/// Runs a script made of shell lines and lisp forms, stopping at the first error.
+pub fn run_script(script: &str, env: &Env, state: &mut State) -> Result<()> {
+    for chunk in script::split(script) {
+        engine::run_line(&chunk, env, state)?;
+    }
 
-    let value = env_get(env, "ALIASES").ok();
+    Ok(())
+}
 
-    match value {
-        Some(ref value) => {
-            match *value.clone() {
-                LispType::HashMap(ref data) => {
-                    match data.get(name) {
-                        Some(value) => {
-                            match **value {
-                                LispType::Strn(ref value_str) => Ok(Some(value_str.to_owned())),
-                                _ => Err(Error::TypeError),
-                            }
-                        },
-                        None => {
-                            Ok(None)
-                        }
-                    }
-                },
-                _ => Err(Error::TypeError),
-            }
-        }
-        None => {
-            Ok(None)
-        }
+/// Whether `name` would run something: an alias, a builtin, a lisp function
+/// or an executable.
+pub fn command_exists(name: &str, env: &Env) -> bool {
+    match evaluator::command_kind(name, env) {
+        Ok(kind) => kind.is_some(),
+        Err(_) => false,
     }
 }
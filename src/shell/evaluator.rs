@@ -1,27 +1,153 @@
-use std::process::Command;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Write};
+use std::process::{Child, ChildStdout, Command, Stdio};
 use std::io::ErrorKind;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
 
-use shell::builtins;
-use types::ShellExpr;
+use shell::alias;
+use shell::brace;
+use shell::builtins::{self, Builtin, Context};
+use shell::state::State;
+use shell::engine;
+use shell::process::{self, Substitution};
+use shell::word::{self, Expansion};
+use types::{self, Connector, LispType, LispValue, Pipeline, Redirect, RedirectKind, ShellExpr, ShellList};
+use env::{Env, env_find, env_get, env_new, env_root, env_set};
 use error::{Error, Result};
+use lisp::evaluator::Evaluator as LispEvaluator;
+use printer::Printer;
 
 pub struct Evaluator;
 
-enum Builtin {
-    Echo,
-    Cd,
-}
-
 enum CommandType {
     Builtin(Builtin),
+    Function(LispValue),
     External(String),
 }
 
-fn resolve_command_type(cmd: String) -> CommandType {
-    match cmd.as_ref() {
-        "cd" => CommandType::Builtin(Builtin::Cd),
-        "echo" => CommandType::Builtin(Builtin::Echo),
-        _ => CommandType::External(cmd),
+/// A command after expanding its words.
+struct Expanded {
+    vars: Vec<(String, String)>,
+    words: Vec<String>,
+    redirects: Vec<Redirect>,
+    /// Exit status of the last command substitution, if there was one.
+    status: Option<i32>,
+}
+
+/// Where a pipeline stage reads its standard input from.
+enum Input {
+    Inherit,
+    Pipe(ChildStdout),
+    Bytes(Vec<u8>),
+    File(File),
+}
+
+/// Where a pipeline stage writes its standard output to.
+enum Output {
+    Inherit,
+    Pipe,
+    File(File),
+}
+
+/// What running a name as a command would do, as reported by `type`.
+pub enum CommandKind {
+    Alias(LispValue),
+    Builtin,
+    Function,
+    File(PathBuf),
+}
+
+/// Function aliases come first, then builtins, then functions defined in
+/// lisp and finally programs on `$PATH`.
+fn resolve_command_type(cmd: String, env: &Env) -> Result<CommandType> {
+    if let Some(value) = alias::lookup(env, &cmd)? {
+        match *value {
+            LispType::Function(_) | LispType::NativeFunction(_) => return Ok(CommandType::Function(value.clone())),
+            _ => {},
+        }
+    }
+
+    if let Some(builtin) = builtins::lookup(&cmd) {
+        return Ok(CommandType::Builtin(builtin));
+    }
+
+    match lisp_command(&cmd, env) {
+        Some(function) => Ok(CommandType::Function(function)),
+        None => Ok(resolve_program(cmd)),
+    }
+}
+
+/// A builtin or a program on `$PATH`, skipping aliases and lisp functions,
+/// for `command NAME`.
+fn resolve_program(cmd: String) -> CommandType {
+    match builtins::lookup(&cmd) {
+        Some(builtin) => CommandType::Builtin(builtin),
+        None => CommandType::External(cmd),
+    }
+}
+
+/// A function defined in lisp by the user and bound to `cmd`. Natives and
+/// the standard library, bound in the root environment, are left out so that
+/// they don't shadow programs with the same name, like `write` or `last`.
+pub fn lisp_command(cmd: &str, env: &Env) -> Option<LispValue> {
+    match env_find(env, cmd) {
+        Some(ref found) if !Rc::ptr_eq(found, &env_root(env)) => {},
+        _ => return None,
+    }
+
+    match env_get(env, cmd) {
+        Ok(value) => {
+            match *value {
+                LispType::Function(_) => Some(value.clone()),
+                _ => None,
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+/// Aliases come first, then builtins, lisp functions and programs on `$PATH`.
+pub fn command_kind(name: &str, env: &Env) -> Result<Option<CommandKind>> {
+    if let Some(value) = alias::lookup(env, name)? {
+        return Ok(Some(CommandKind::Alias(value)));
+    }
+
+    if builtins::lookup(name).is_some() {
+        return Ok(Some(CommandKind::Builtin));
+    }
+
+    if lisp_command(name, env).is_some() {
+        return Ok(Some(CommandKind::Function));
+    }
+
+    Ok(find_executable(name).map(CommandKind::File))
+}
+
+pub fn find_executable(cmd: &str) -> Option<PathBuf> {
+    if cmd.contains('/') {
+        let path = Path::new(cmd);
+
+        return if is_executable(path) { Some(path.to_path_buf()) } else { None };
+    }
+
+    let paths = env::var_os("PATH")?;
+
+    env::split_paths(&paths)
+        .map(|dir| dir.join(cmd))
+        .find(|path| is_executable(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    match path.metadata() {
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
     }
 }
 
@@ -30,23 +156,418 @@ impl Evaluator {
         Evaluator
     }
 
-    pub fn eval(&self, mut expr: ShellExpr) -> Result<()> {
-        let args = expr.words.split_off(1);
-        let cmd = expr.words.remove(0);
+    pub fn eval(&self, list: ShellList, env: Env, state: &mut State) -> Result<()> {
+        self.eval_list(list, &env, state, &mut None)?;
+
+        Ok(())
+    }
 
-        let cmd_type = resolve_command_type(cmd);
+    /// Runs `input` for a command substitution, returning its standard output
+    /// and exit status.
+    fn substitute(&self, input: &str, env: &Env, state: &mut State) -> Result<(String, i32)> {
+        let list = engine::parse(input, env)?;
+        let mut captured = Some(vec![]);
 
-        match cmd_type {
-            CommandType::External(name) => run_external(name, args),
-            CommandType::Builtin(name) => run_builtin(name, args),
+        let status = self.eval_list(list, env, state, &mut captured)?;
+        let output = captured.unwrap_or_default();
+
+        Ok((String::from_utf8_lossy(&output).into_owned(), status))
+    }
+
+    /// Runs every pipeline of the list, appending the output of the last
+    /// stages to `captured` if given, and returns the last exit status.
+    ///
+    /// A pipeline that fails, like a missing command or a builtin error, is
+    /// reported and gets a non-zero status instead of stopping the list.
+    /// Only `exit` stops it.
+    fn eval_list(&self, list: ShellList, env: &Env, state: &mut State, captured: &mut Option<Vec<u8>>) -> Result<i32> {
+        let mut status = 0;
+
+        for (connector, pipeline) in list.items {
+            if connector == Connector::And && status != 0 {
+                continue;
+            }
+
+            status = match self.eval_pipeline(pipeline, env, state, captured) {
+                Ok(status) => status,
+                Err(e @ Error::Exit(_)) | Err(e @ Error::Interrupted) => return Err(e),
+                Err(e) => {
+                    e.report(state.json_errors);
+                    e.status()
+                },
+            };
+            state.last_status = status;
+        }
+
+        Ok(status)
+    }
+
+    /// Runs every command of the pipeline and returns the exit status of the last one.
+    fn eval_pipeline(&self, pipeline: Pipeline, env: &Env, state: &mut State, captured: &mut Option<Vec<u8>>) -> Result<i32> {
+        let mut children = vec![];
+        let mut substitutions = vec![];
+
+        let output = if captured.is_some() { Output::Pipe } else { Output::Inherit };
+        let result = self.spawn_pipeline(pipeline, env, state, Input::Inherit, output, &mut children, &mut substitutions);
+
+        let result = match (result, captured.as_mut()) {
+            (Ok((status, output)), Some(buffer)) => read_output(output, buffer).map(|_| status),
+            (result, _) => result.map(|(status, _)| status),
+        };
+
+        let mut status = 0;
+        for mut child in children {
+            status = exit_code(child.wait()?);
+        }
+
+        for substitution in substitutions {
+            substitution.finish()?;
+        }
+
+        match result? {
+            Some(status) => Ok(status),
+            None => Ok(status),
+        }
+    }
+
+    /// Starts the stages of `pipeline`, returning the exit status of the last
+    /// one, unless it is a child process that still has to be waited for,
+    /// and what it writes to `output`.
+    fn spawn_pipeline(&self, pipeline: Pipeline, env: &Env, state: &mut State, mut input: Input, output: Output, children: &mut Vec<Child>, substitutions: &mut Vec<Substitution>) -> Result<(Option<i32>, Input)> {
+        let mut last_output = Some(output);
+        let count = pipeline.commands.len();
+
+        for (index, expr) in pipeline.commands.into_iter().enumerate() {
+            let last = index + 1 == count;
+            let first = substitutions.len();
+            let Expanded { vars, words: mut args, redirects, status } = self.expand_command(expr, env, state, substitutions)?;
+
+            if args.is_empty() {
+                // assignments without a command set shell variables
+                for (name, value) in vars {
+                    state.set_var(name, value);
+                }
+
+                if last {
+                    return Ok((Some(status.unwrap_or(0)), Input::Bytes(vec![])));
+                }
+                input = Input::Bytes(vec![]);
+                continue;
+            }
+            let cmd = args.remove(0);
+
+            let command_type = if cmd == "command" && !args.is_empty() {
+                resolve_program(args.remove(0))
+            } else {
+                resolve_command_type(cmd, env)?
+            };
+
+            let output = if last { last_output.take().unwrap_or(Output::Inherit) } else { Output::Pipe };
+            let (stage_input, output) = open_redirects(&redirects, input, output)?;
+
+            input = match command_type {
+                CommandType::External(name) => {
+                    let fds = substitutions[first..].iter().map(Substitution::fd).collect();
+                    let mut child = run_external(name, args, &vars, fds, stage_input, output)?;
+                    let output = match child.stdout.take() {
+                        Some(output) => Input::Pipe(output),
+                        None => Input::Bytes(vec![]),
+                    };
+
+                    children.push(child);
+
+                    if last {
+                        return Ok((None, output));
+                    }
+
+                    output
+                },
+                CommandType::Builtin(builtin) => {
+                    with_vars(&vars, || {
+                        self.capture(output, |out| run_builtin(builtin, args, env, state, out))
+                    })?
+                },
+                CommandType::Function(function) => {
+                    let stdin = read_input(stage_input)?;
+
+                    with_vars(&vars, || {
+                        self.capture(output, |out| run_function(function, args, stdin, env, out))
+                    })?
+                },
+            };
         }
 
+        Ok((Some(0), input))
+    }
+
+    /// Starts `<(input)`, whose output the main command reads from a pipe.
+    ///
+    /// A single pipeline runs alongside the main command; lists are run to
+    /// completion first.
+    fn substitute_input(&self, input: &str, env: &Env, state: &mut State) -> Result<Substitution> {
+        let mut list = engine::parse(input, env)?;
+        let mut children = vec![];
+        let mut nested = vec![];
+
+        let output = if list.items.len() == 1 {
+            let (_, pipeline) = list.items.remove(0);
+
+            self.spawn_pipeline(pipeline, env, state, Input::Inherit, Output::Pipe, &mut children, &mut nested)?.1
+        } else {
+            let mut captured = Some(vec![]);
+            self.eval_list(list, env, state, &mut captured)?;
+
+            Input::Bytes(captured.unwrap_or_default())
+        };
+
+        let file = match output {
+            Input::Pipe(output) => unsafe { File::from_raw_fd(output.into_raw_fd()) },
+            Input::Bytes(bytes) => process::pipe_from(bytes)?,
+            Input::File(file) => file,
+            Input::Inherit => process::pipe_from(vec![])?,
+        };
+
+        Ok(Substitution::new(file, children, nested))
     }
+
+    /// Starts `>(input)`, which reads what the main command writes to a pipe.
+    ///
+    /// Only a single pipeline can be used. Lisp functions read their whole
+    /// input before running, so they can't start it.
+    fn substitute_output(&self, input: &str, env: &Env, state: &mut State) -> Result<Substitution> {
+        let mut list = engine::parse(input, env)?;
+        if list.items.len() != 1 {
+            return Err(Error::ParseError);
+        }
+        let (_, pipeline) = list.items.remove(0);
+
+        let (read, write) = process::pipe()?;
+        let mut children = vec![];
+        let mut nested = vec![];
+
+        self.spawn_pipeline(pipeline, env, state, Input::File(read), Output::Inherit, &mut children, &mut nested)?;
+
+        Ok(Substitution::new(write, children, nested))
+    }
+
+    /// Expands the assignments, words and redirection targets of a command.
+    fn expand_command(&self, expr: ShellExpr, env: &Env, state: &mut State, substitutions: &mut Vec<Substitution>) -> Result<Expanded> {
+        let mut status = None;
+
+        let (vars, words, redirects) = {
+            let mut expander = |expansion: &Expansion| {
+                match *expansion {
+                    Expansion::Command(input) => {
+                        let (output, code) = state.nested(|state| self.substitute(input, env, state))?;
+
+                        state.last_status = code;
+                        status = Some(code);
+
+                        Ok(output)
+                    },
+                    Expansion::ProcessInput(input) => {
+                        let substitution = state.nested(|state| self.substitute_input(input, env, state))?;
+                        let path = substitution.path();
+
+                        substitutions.push(substitution);
+
+                        Ok(path)
+                    },
+                    Expansion::ProcessOutput(input) => {
+                        let substitution = state.nested(|state| self.substitute_output(input, env, state))?;
+                        let path = substitution.path();
+
+                        substitutions.push(substitution);
+
+                        Ok(path)
+                    },
+                    Expansion::Variable(name) => Ok(state.var(name).unwrap_or_default()),
+                    Expansion::Status => Ok(state.last_status.to_string()),
+                }
+            };
+
+            let mut vars = vec![];
+            for assignment in expr.assignments {
+                vars.push((assignment.name, word::expand_single(&assignment.value, &mut expander)?));
+            }
+
+            let mut words = vec![];
+            for w in expr.words {
+                for w in brace::expand(&w) {
+                    words.extend(word::expand(&w, &mut expander)?);
+                }
+            }
+
+            let mut redirects = vec![];
+            for redirect in expr.redirects {
+                let target = match redirect.kind {
+                    RedirectKind::HereDoc { expand: true } => word::expand_here_doc(&redirect.target, &mut expander)?,
+                    RedirectKind::HereDoc { expand: false } => redirect.target,
+                    RedirectKind::HereString => word::expand_single(&redirect.target, &mut expander)? + "\n",
+                    _ => word::expand_single(&redirect.target, &mut expander)?,
+                };
+
+                redirects.push(Redirect {
+                    kind: redirect.kind,
+                    target: target,
+                });
+            }
+
+            (vars, words, redirects)
+        };
+
+        Ok(Expanded {
+            vars: vars,
+            words: words,
+            redirects: redirects,
+            status: status,
+        })
+    }
+
+    /// Runs `f` writing to the stage's output, returning the input of the next stage.
+    fn capture<F>(&self, output: Output, f: F) -> Result<Input> where F: FnOnce(&mut dyn Write) -> Result<()> {
+        match output {
+            Output::Inherit => {
+                let stdout = io::stdout();
+                let mut out = stdout.lock();
+
+                f(&mut out)?;
+                out.flush()?;
+
+                Ok(Input::Inherit)
+            },
+            Output::Pipe => {
+                let mut buffer = vec![];
+
+                f(&mut buffer)?;
+
+                Ok(Input::Bytes(buffer))
+            },
+            Output::File(mut file) => {
+                f(&mut file)?;
+
+                Ok(Input::Bytes(vec![]))
+            },
+        }
+    }
+}
+
+/// Applies the redirections of a stage on top of its pipeline input and output.
+fn open_redirects(redirects: &[Redirect], mut input: Input, mut output: Output) -> Result<(Input, Output)> {
+    for redirect in redirects {
+        match redirect.kind {
+            RedirectKind::Input => {
+                input = Input::File(File::open(&redirect.target)?);
+            },
+            RedirectKind::Output => {
+                output = Output::File(File::create(&redirect.target)?);
+            },
+            RedirectKind::Append => {
+                output = Output::File(OpenOptions::new().append(true).create(true).open(&redirect.target)?);
+            },
+            RedirectKind::HereDoc { .. } | RedirectKind::HereString => {
+                input = Input::Bytes(redirect.target.as_bytes().to_vec());
+            },
+        }
+    }
+
+    Ok((input, output))
+}
+
+/// Appends what the last stage of a pipeline writes to `buffer`.
+fn read_output(output: Input, buffer: &mut Vec<u8>) -> Result<()> {
+    match output {
+        Input::Inherit => {},
+        Input::Pipe(mut output) => { output.read_to_end(buffer)?; },
+        Input::Bytes(bytes) => buffer.extend(bytes),
+        Input::File(mut file) => { file.read_to_end(buffer)?; },
+    }
+
+    Ok(())
 }
 
-fn run_external(cmd: String, args: Vec<String>) -> Result<()> {
+/// Reads the whole input of a stage, or nothing when it reads from the terminal.
+fn read_input(input: Input) -> Result<Option<String>> {
+    let mut buffer = String::new();
+
+    match input {
+        Input::Inherit => return Ok(None),
+        Input::Pipe(mut output) => { output.read_to_string(&mut buffer)?; },
+        Input::Bytes(bytes) => buffer = String::from_utf8_lossy(&bytes).into_owned(),
+        Input::File(mut file) => { file.read_to_string(&mut buffer)?; },
+    }
+
+    Ok(Some(buffer))
+}
+
+/// Runs `f` with `vars` set in the environment, restoring the previous
+/// values afterwards.
+fn with_vars<T, F>(vars: &[(String, String)], f: F) -> T where F: FnOnce() -> T {
+    let previous: Vec<_> = vars.iter().map(|&(ref name, _)| (name.clone(), env::var_os(name))).collect();
+
+    for &(ref name, ref value) in vars {
+        env::set_var(name, value);
+    }
+
+    let result = f();
+
+    for (name, value) in previous.into_iter().rev() {
+        match value {
+            Some(value) => env::set_var(name, value),
+            None => env::remove_var(name),
+        }
+    }
+
+    result
+}
+
+fn exit_code(status: ::std::process::ExitStatus) -> i32 {
+    match status.code() {
+        Some(code) => code,
+        None => 128 + status.signal().unwrap_or(0),
+    }
+}
+
+/// Starts an external command. `fds` are the pipes of its process
+/// substitutions, which only this command inherits.
+fn run_external(cmd: String, args: Vec<String>, vars: &[(String, String)], fds: Vec<RawFd>, input: Input, output: Output) -> Result<Child> {
     let mut command = Command::new(&cmd);
     command.args(&args);
+    command.envs(vars.iter().cloned());
+
+    if !fds.is_empty() {
+        unsafe {
+            command.pre_exec(move || {
+                for &fd in &fds {
+                    process::keep_open(fd)?;
+                }
+
+                Ok(())
+            });
+        }
+    }
+
+    let pending = match input {
+        Input::Inherit => None,
+        Input::Pipe(output) => {
+            command.stdin(Stdio::from(output));
+            None
+        },
+        Input::Bytes(bytes) => {
+            command.stdin(Stdio::piped());
+            Some(bytes)
+        },
+        Input::File(file) => {
+            command.stdin(Stdio::from(file));
+            None
+        },
+    };
+
+    match output {
+        Output::Inherit => {},
+        Output::Pipe => { command.stdout(Stdio::piped()); },
+        Output::File(file) => { command.stdout(Stdio::from(file)); },
+    }
 
     let mut child = match command.spawn() {
         Ok(result) => result,
@@ -62,14 +583,63 @@ fn run_external(cmd: String, args: Vec<String>) -> Result<()> {
         }
     };
 
-    child.wait()?;
+    if let (Some(bytes), Some(mut stdin)) = (pending, child.stdin.take()) {
+        thread::spawn(move || stdin.write_all(&bytes));
+    }
 
-    Ok(())
+    Ok(child)
 }
 
-fn run_builtin(builtin: Builtin, args: Vec<String>) -> Result<()> {
-    match builtin {
-        Builtin::Cd => builtins::cd::run(args),
-        Builtin::Echo => builtins::echo::run(args),
+/// Calls a lisp function with the words as string arguments.
+///
+/// The stage's input is bound to `*stdin*` (nil when reading from the
+/// terminal) and the result is displayed on the output, one line per element
+/// for lists and vectors.
+fn run_function(function: LispValue, args: Vec<String>, stdin: Option<String>, env: &Env, out: &mut dyn Write) -> Result<()> {
+    let args: Vec<LispValue> = args.into_iter().map(types::string).collect();
+    let stdin = match stdin {
+        Some(input) => types::string(input),
+        None => types::nil(),
+    };
+
+    // `*stdin*` is only bound for this call, in a scope between the function
+    // and the environment it closes over
+    let call_env = env_new(Some(env.clone()));
+    env_set(&call_env, "*stdin*", stdin.clone());
+
+    let function = match *function {
+        LispType::Function(ref data) => {
+            let scope = env_new(Some(data.env.clone()));
+            env_set(&scope, "*stdin*", stdin);
+
+            data.with_env(scope)
+        },
+        _ => function.clone(),
+    };
+
+    let result = LispEvaluator::new().apply_function(function, &args, &call_env)?;
+    let printer = Printer::new();
+
+    match *result {
+        LispType::Nil => {},
+        LispType::List(ref values) | LispType::Vector(ref values) => {
+            for value in values {
+                writeln!(out, "{}", printer.print(value, false))?;
+            }
+        },
+        _ => {
+            writeln!(out, "{}", printer.print(&result, false))?;
+        },
     }
+
+    Ok(())
+}
+
+fn run_builtin(builtin: Builtin, args: Vec<String>, env: &Env, state: &mut State, out: &mut dyn Write) -> Result<()> {
+    let mut context = Context {
+        env: env,
+        state: state,
+    };
+
+    builtin(args, &mut context, out)
 }
@@ -0,0 +1,167 @@
+use std::str::Lines;
+
+use error::Result;
+use shell::lexer::tokenize;
+use shell::token::Token;
+use shell::word;
+
+/// Tokenizes shell input that may span several lines.
+///
+/// The lines following a command with here-documents are their bodies, up to
+/// each delimiter, and are attached to the command as `Token::HereDoc`. Other
+/// lines are separate commands, unless the previous one ends with `&&` or `|`.
+pub fn tokenize_lines(input: &str) -> Result<Vec<Token>> {
+    Ok(read(input)?.0)
+}
+
+/// Whether every here-document in `input` has been terminated by its delimiter.
+pub fn is_complete(input: &str) -> bool {
+    match read(input) {
+        Ok((_, complete)) => complete,
+        Err(_) => true,
+    }
+}
+
+fn read(input: &str) -> Result<(Vec<Token>, bool)> {
+    let mut tokens = vec![];
+    let mut complete = true;
+    let mut lines = input.lines();
+
+    while let Some(line) = lines.next() {
+        let line_tokens = tokenize(line)?;
+
+        match tokens.last() {
+            None | Some(&Token::Semicolon) | Some(&Token::And) | Some(&Token::Pipe) => {},
+            Some(_) => if !line_tokens.is_empty() {
+                tokens.push(Token::Semicolon);
+            },
+        }
+
+        let mut line_tokens = line_tokens.into_iter().peekable();
+
+        while let Some(token) = line_tokens.next() {
+            let strip_tabs = match token {
+                Token::RedirectHereDoc => false,
+                Token::RedirectHereDocStrip => true,
+                _ => {
+                    tokens.push(token);
+                    continue;
+                },
+            };
+
+            let delimiter = match line_tokens.peek() {
+                Some(&Token::Ident(ref delimiter)) => delimiter.clone(),
+                _ => {
+                    tokens.push(token);
+                    continue;
+                },
+            };
+            line_tokens.next();
+
+            let (body, terminated) = read_body(&mut lines, &unquote(&delimiter)?, strip_tabs);
+            complete = complete && terminated;
+
+            tokens.push(Token::HereDoc {
+                body: body,
+                expand: !is_quoted(&delimiter),
+            });
+        }
+    }
+
+    Ok((tokens, complete))
+}
+
+/// Reads lines up to `delimiter`, returning the body and whether the
+/// delimiter was found.
+fn read_body(lines: &mut Lines, delimiter: &str, strip_tabs: bool) -> (String, bool) {
+    let mut body = String::new();
+
+    for line in lines {
+        let line = if strip_tabs { line.trim_start_matches('\t') } else { line };
+
+        if line == delimiter {
+            return (body, true);
+        }
+
+        body.push_str(line);
+        body.push('\n');
+    }
+
+    (body, false)
+}
+
+fn unquote(delimiter: &str) -> Result<String> {
+    word::expand_single(delimiter, &mut |expansion: &word::Expansion| {
+        Ok(match *expansion {
+            word::Expansion::Command(command) => format!("$({})", command),
+            word::Expansion::Variable(name) => format!("${}", name),
+            word::Expansion::Status => "$?".to_owned(),
+            word::Expansion::ProcessInput(command) => format!("<({})", command),
+            word::Expansion::ProcessOutput(command) => format!(">({})", command),
+        })
+    })
+}
+
+/// The body of a here-document is expanded unless any part of its delimiter
+/// is quoted.
+fn is_quoted(delimiter: &str) -> bool {
+    delimiter.contains(|c| c == '\'' || c == '"' || c == '\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn here_doc(body: &str, expand: bool) -> Token {
+        Token::HereDoc {
+            body: body.to_owned(),
+            expand: expand,
+        }
+    }
+
+    #[test]
+    fn read_here_document() {
+        assert_eq!(tokenize_lines("cat <<EOF\na $b\n  c\nEOF\necho").unwrap(),
+                   vec![Token::Ident("cat".to_owned()),
+                        here_doc("a $b\n  c\n", true),
+                        Token::Semicolon,
+                        Token::Ident("echo".to_owned())]);
+    }
+
+    #[test]
+    fn read_quoted_delimiter() {
+        assert_eq!(tokenize_lines("cat <<'EOF'\n$a\nEOF").unwrap(),
+                   vec![Token::Ident("cat".to_owned()), here_doc("$a\n", false)]);
+        assert_eq!(tokenize_lines("cat <<\"E\"OF\n$a\nEOF").unwrap(),
+                   vec![Token::Ident("cat".to_owned()), here_doc("$a\n", false)]);
+    }
+
+    #[test]
+    fn read_strips_tabs() {
+        assert_eq!(tokenize_lines("cat <<-EOF\n\t\ta\n\tEOF").unwrap(),
+                   vec![Token::Ident("cat".to_owned()), here_doc("a\n", true)]);
+    }
+
+    #[test]
+    fn read_several_here_documents() {
+        assert_eq!(tokenize_lines("a <<A | b <<B\n1\nA\n2\nB").unwrap(),
+                   vec![Token::Ident("a".to_owned()),
+                        here_doc("1\n", true),
+                        Token::Pipe,
+                        Token::Ident("b".to_owned()),
+                        here_doc("2\n", true)]);
+    }
+
+    #[test]
+    fn continue_after_and() {
+        assert_eq!(tokenize_lines("a &&\nb").unwrap(), tokenize("a && b").unwrap());
+    }
+
+    #[test]
+    fn complete_input() {
+        assert!(is_complete("ls"));
+        assert!(is_complete("cat <<EOF\na\nEOF"));
+        assert!(!is_complete("cat <<EOF"));
+        assert!(!is_complete("cat <<EOF\na\nEOF \n"));
+    }
+}
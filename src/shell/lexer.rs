@@ -1,8 +1,9 @@
 use std::str;
 
-use nom::IResult;
+use nom::{ErrorKind, IResult};
 
 use shell::token::Token;
+use shell::word::word_length;
 use error::Result;
 use error::Error;
 
@@ -15,15 +16,66 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
     }
 }
 
-named!(lex_tokens<&str, Vec<Token>>, ws!(many0!(lex_ident)));
+named!(lex_tokens<&str, Vec<Token>>, ws!(many0!(lex_token)));
 
-named!(lex_ident<&str, Token>,
-    do_parse!(
-	w: re_capture!(r#"^((?:[[:word:]]|/|-|\+|\*|%|=|\.)+)|^("(?:\\.|[^\\"])*")"#) >>
-	(Token::Ident(w[0].to_owned()))
+named!(lex_token<&str, Token>,
+    alt_complete!(
+	lex_ident |
+	lex_and |
+	lex_pipe |
+	lex_semicolon |
+	lex_redirect_here_string |
+	lex_redirect_here_doc_strip |
+	lex_redirect_here_doc |
+	lex_redirect_append |
+	lex_redirect_output |
+	lex_redirect_input
     )
 );
 
+named!(lex_and<&str, Token>,
+    do_parse!(tag!("&&") >> (Token::And))
+);
+
+named!(lex_pipe<&str, Token>,
+    do_parse!(tag!("|") >> (Token::Pipe))
+);
+
+named!(lex_semicolon<&str, Token>,
+    do_parse!(tag!(";") >> (Token::Semicolon))
+);
+
+named!(lex_redirect_here_string<&str, Token>,
+    do_parse!(tag!("<<<") >> (Token::RedirectHereString))
+);
+
+named!(lex_redirect_here_doc_strip<&str, Token>,
+    do_parse!(tag!("<<-") >> (Token::RedirectHereDocStrip))
+);
+
+named!(lex_redirect_here_doc<&str, Token>,
+    do_parse!(tag!("<<") >> (Token::RedirectHereDoc))
+);
+
+named!(lex_redirect_append<&str, Token>,
+    do_parse!(tag!(">>") >> (Token::RedirectAppend))
+);
+
+named!(lex_redirect_output<&str, Token>,
+    do_parse!(tag!(">") >> (Token::RedirectOutput))
+);
+
+named!(lex_redirect_input<&str, Token>,
+    do_parse!(tag!("<") >> (Token::RedirectInput))
+);
+
+fn lex_ident(input: &str) -> IResult<&str, Token> {
+    match word_length(input) {
+        0 => IResult::Error(error_position!(ErrorKind::Custom(0), input)),
+        length => IResult::Done(&input[length..], Token::Ident(input[..length].to_owned())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +127,89 @@ mod tests {
         assert_eq!(tokenize("\"abc def\"").unwrap(), vec!(Token::Ident("\"abc def\"".to_owned())));
     }
 
+    #[test]
+    fn lex_ident_with_single_quotes() {
+        assert_eq!(tokenize("'a \"b'").unwrap(), vec!(Token::Ident("'a \"b'".to_owned())));
+    }
+
+    #[test]
+    fn lex_ident_with_quoted_parts() {
+        assert_eq!(tokenize("ll='ls -la'").unwrap(), vec!(Token::Ident("ll='ls -la'".to_owned())));
+    }
+
+    #[test]
+    fn lex_ident_with_command_substitution() {
+        assert_eq!(tokenize("cd $(git rev-parse --show-toplevel)/src `pwd`").unwrap(),
+                   vec![Token::Ident("cd".to_owned()),
+                        Token::Ident("$(git rev-parse --show-toplevel)/src".to_owned()),
+                        Token::Ident("`pwd`".to_owned())]);
+    }
+
+    #[test]
+    fn lex_separators() {
+        assert_eq!(tokenize("a; b && c | d").unwrap(),
+                   vec![Token::Ident("a".to_owned()),
+                        Token::Semicolon,
+                        Token::Ident("b".to_owned()),
+                        Token::And,
+                        Token::Ident("c".to_owned()),
+                        Token::Pipe,
+                        Token::Ident("d".to_owned())]);
+    }
+
+    #[test]
+    fn lex_separators_without_spaces() {
+        assert_eq!(tokenize("a;b|c").unwrap(),
+                   vec![Token::Ident("a".to_owned()),
+                        Token::Semicolon,
+                        Token::Ident("b".to_owned()),
+                        Token::Pipe,
+                        Token::Ident("c".to_owned())]);
+    }
+
+    #[test]
+    fn lex_redirections() {
+        assert_eq!(tokenize("a <in >out >>log").unwrap(),
+                   vec![Token::Ident("a".to_owned()),
+                        Token::RedirectInput,
+                        Token::Ident("in".to_owned()),
+                        Token::RedirectOutput,
+                        Token::Ident("out".to_owned()),
+                        Token::RedirectAppend,
+                        Token::Ident("log".to_owned())]);
+    }
+
+    #[test]
+    fn lex_here_documents() {
+        assert_eq!(tokenize("a <<EOF <<-'END' <<<word").unwrap(),
+                   vec![Token::Ident("a".to_owned()),
+                        Token::RedirectHereDoc,
+                        Token::Ident("EOF".to_owned()),
+                        Token::RedirectHereDocStrip,
+                        Token::Ident("'END'".to_owned()),
+                        Token::RedirectHereString,
+                        Token::Ident("word".to_owned())]);
+    }
+
+    #[test]
+    fn lex_braces() {
+        assert_eq!(tokenize("mkdir -p src/{lisp,shell} {a..c}").unwrap(),
+                   vec![Token::Ident("mkdir".to_owned()),
+                        Token::Ident("-p".to_owned()),
+                        Token::Ident("src/{lisp,shell}".to_owned()),
+                        Token::Ident("{a..c}".to_owned())]);
+    }
+
+    #[test]
+    fn lex_process_substitution() {
+        assert_eq!(tokenize("diff <(ls a) <(ls b) >out").unwrap(),
+                   vec![Token::Ident("diff".to_owned()),
+                        Token::Ident("<(ls a)".to_owned()),
+                        Token::Ident("<(ls b)".to_owned()),
+                        Token::RedirectOutput,
+                        Token::Ident("out".to_owned())]);
+    }
+
     #[test]
     fn lex_illegal() {
         match tokenize("^").unwrap_err() {
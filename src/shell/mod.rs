@@ -1,7 +1,14 @@
 mod evaluator;
-mod lexer;
 mod parser;
-mod token;
 mod builtins;
+mod alias;
+mod script;
+mod process;
+mod brace;
 
 pub mod engine;
+pub mod state;
+pub mod lexer;
+pub mod token;
+pub mod word;
+pub mod heredoc;
@@ -1,8 +1,10 @@
 use std::collections::VecDeque;
 
 use shell::token::Token;
-use error::Result;
+use error::{Error, Result};
 use types;
+use types::{Assignment, Connector, Redirect, RedirectKind};
+use shell::word;
 
 pub struct Parser {
     tokens: VecDeque<Token>,
@@ -19,29 +21,121 @@ impl Parser {
         self.tokens.extend(tokens);
     }
 
-    pub fn parse(&mut self) -> Result<types::ShellExpr> {
-        self.read_shell()
+    pub fn parse(&mut self) -> Result<types::ShellList> {
+        let result = self.read_list();
+
+        self.tokens.clear();
+
+        result
     }
 
     fn next(&mut self) -> Option<Token> {
         self.tokens.pop_front()
     }
 
+    fn peek(&self) -> Option<Token> {
+        self.tokens.front().cloned()
+    }
+
+    fn read_list(&mut self) -> Result<types::ShellList> {
+        let mut items = vec![];
+        let mut connector = Connector::Always;
+
+        while self.peek().is_some() {
+            items.push((connector, self.read_pipeline()?));
+
+            match self.next() {
+                None => break,
+                Some(Token::Semicolon) => {
+                    connector = Connector::Always;
+                },
+                Some(Token::And) => {
+                    if self.peek().is_none() {
+                        return Err(Error::ParseError);
+                    }
+                    connector = Connector::And;
+                },
+                Some(_) => return Err(Error::ParseError),
+            }
+        }
+
+        Ok(types::ShellList {
+            items: items,
+        })
+    }
+
+    fn read_pipeline(&mut self) -> Result<types::Pipeline> {
+        let mut commands = vec![self.read_shell()?];
+
+        while let Some(Token::Pipe) = self.peek() {
+            self.next();
+            commands.push(self.read_shell()?);
+        }
+
+        Ok(types::Pipeline {
+            commands: commands,
+        })
+    }
+
     fn read_shell(&mut self) -> Result<types::ShellExpr> {
+        let mut assignments = vec![];
         let mut words = vec![];
+        let mut redirects = vec![];
 
-        while let Some(token) = self.next() {
-            match token {
-                Token::Ident(token) => {
-                    words.push(token);
+        loop {
+            match self.peek() {
+                Some(Token::Ident(token)) => {
+                    self.next();
+
+                    match word::split_assignment(&token) {
+                        Some((name, value)) if words.is_empty() => {
+                            assignments.push(Assignment {
+                                name: name.to_owned(),
+                                value: value.to_owned(),
+                            });
+                        },
+                        _ => words.push(token),
+                    }
+                },
+                Some(Token::RedirectInput) => redirects.push(self.read_redirect(RedirectKind::Input)?),
+                Some(Token::RedirectOutput) => redirects.push(self.read_redirect(RedirectKind::Output)?),
+                Some(Token::RedirectAppend) => redirects.push(self.read_redirect(RedirectKind::Append)?),
+                Some(Token::RedirectHereString) => redirects.push(self.read_redirect(RedirectKind::HereString)?),
+                Some(Token::HereDoc { body, expand }) => {
+                    self.next();
+                    redirects.push(Redirect {
+                        kind: RedirectKind::HereDoc { expand: expand },
+                        target: body,
+                    });
                 },
+                _ => break,
             }
         }
 
+        if words.is_empty() && (assignments.is_empty() || !redirects.is_empty()) {
+            return Err(Error::ParseError);
+        }
+
         Ok(types::ShellExpr {
+            assignments: assignments,
             words: words,
+            redirects: redirects,
         })
     }
+
+    fn read_redirect(&mut self, kind: RedirectKind) -> Result<Redirect> {
+        self.next();
+
+        match self.next() {
+            Some(Token::Ident(target)) => {
+                Ok(Redirect {
+                    kind: kind,
+                    target: target,
+                })
+            },
+            _ => Err(Error::ParseError),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -50,7 +144,7 @@ mod tests {
     use super::*;
     use shell::lexer::tokenize;
 
-    fn parse(input: &str) -> Result<ShellExpr> {
+    fn parse(input: &str) -> Result<ShellList> {
         let tokens = tokenize(input).unwrap();
 
         let mut parser = Parser::new();
@@ -59,23 +153,195 @@ mod tests {
         parser.parse()
     }
 
-    fn assert_input_with_ast(input: &str, expected: ShellExpr) {
+    fn assert_input_with_ast(input: &str, expected: ShellList) {
         let parsed = parse(input).unwrap();
 
         assert_eq!(parsed, expected);
     }
 
+    fn command(words: &[&str]) -> ShellExpr {
+        ShellExpr {
+            assignments: vec![],
+            words: words.iter().map(|word| word.to_string()).collect(),
+            redirects: vec![],
+        }
+    }
+
+    fn pipeline(commands: Vec<ShellExpr>) -> Pipeline {
+        Pipeline {
+            commands: commands,
+        }
+    }
+
     #[test]
     fn parse_shell_expr() {
         let input = "ls -la file";
-        let expected = ShellExpr {
-            words: vec![
-                "ls".to_owned(),
-                "-la".to_owned(),
-                "file".to_owned()
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    ShellExpr {
+                        assignments: vec![],
+                        words: vec![
+                            "ls".to_owned(),
+                            "-la".to_owned(),
+                            "file".to_owned()
+                        ],
+                        redirects: vec![],
+                    },
+                ])),
+            ],
+        };
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_empty_input() {
+        assert_input_with_ast("", ShellList { items: vec![] });
+    }
+
+    #[test]
+    fn parse_pipeline() {
+        let input = "ls | grep a | wc -l";
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    command(&["ls"]),
+                    command(&["grep", "a"]),
+                    command(&["wc", "-l"]),
+                ])),
             ],
         };
 
         assert_input_with_ast(input, expected);
     }
+
+    #[test]
+    fn parse_list() {
+        let input = "cd dir; make && make install;";
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![command(&["cd", "dir"])])),
+                (Connector::Always, pipeline(vec![command(&["make"])])),
+                (Connector::And, pipeline(vec![command(&["make", "install"])])),
+            ],
+        };
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_redirections() {
+        let input = "sort < in > out | tee >> log";
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    ShellExpr {
+                        assignments: vec![],
+                        words: vec!["sort".to_owned()],
+                        redirects: vec![
+                            Redirect { kind: RedirectKind::Input, target: "in".to_owned() },
+                            Redirect { kind: RedirectKind::Output, target: "out".to_owned() },
+                        ],
+                    },
+                    ShellExpr {
+                        assignments: vec![],
+                        words: vec!["tee".to_owned()],
+                        redirects: vec![
+                            Redirect { kind: RedirectKind::Append, target: "log".to_owned() },
+                        ],
+                    },
+                ])),
+            ],
+        };
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_assignments() {
+        let input = "A=1 B='x y' cmd C=2";
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    ShellExpr {
+                        assignments: vec![
+                            Assignment { name: "A".to_owned(), value: "1".to_owned() },
+                            Assignment { name: "B".to_owned(), value: "'x y'".to_owned() },
+                        ],
+                        words: vec!["cmd".to_owned(), "C=2".to_owned()],
+                        redirects: vec![],
+                    },
+                ])),
+            ],
+        };
+
+        assert_input_with_ast(input, expected);
+    }
+
+    #[test]
+    fn parse_assignments_without_command() {
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    ShellExpr {
+                        assignments: vec![Assignment { name: "A".to_owned(), value: "1".to_owned() }],
+                        words: vec![],
+                        redirects: vec![],
+                    },
+                ])),
+            ],
+        };
+
+        assert_input_with_ast("A=1", expected);
+        assert!(parse("A=1 > out").is_err());
+    }
+
+    #[test]
+    fn parse_here_documents() {
+        let mut parser = Parser::new();
+        parser.add_tokens(vec![
+            Token::Ident("cat".to_owned()),
+            Token::HereDoc { body: "a\n".to_owned(), expand: false },
+            Token::RedirectHereString,
+            Token::Ident("b".to_owned()),
+        ]);
+
+        let expected = ShellList {
+            items: vec![
+                (Connector::Always, pipeline(vec![
+                    ShellExpr {
+                        assignments: vec![],
+                        words: vec!["cat".to_owned()],
+                        redirects: vec![
+                            Redirect { kind: RedirectKind::HereDoc { expand: false }, target: "a\n".to_owned() },
+                            Redirect { kind: RedirectKind::HereString, target: "b".to_owned() },
+                        ],
+                    },
+                ])),
+            ],
+        };
+
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_here_document_without_body() {
+        assert!(parse("cat <<").is_err());
+        assert!(parse("cat <<EOF").is_err());
+    }
+
+    #[test]
+    fn parse_redirection_without_target() {
+        assert!(parse("ls >").is_err());
+        assert!(parse("ls > | cat").is_err());
+    }
+
+    #[test]
+    fn parse_missing_command() {
+        assert!(parse("|").is_err());
+        assert!(parse("ls |").is_err());
+        assert!(parse("ls &&").is_err());
+        assert!(parse("; ls").is_err());
+    }
 }
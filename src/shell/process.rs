@@ -0,0 +1,85 @@
+extern crate libc;
+
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::process::Child;
+use std::thread;
+
+use error::{Error, Result};
+
+/// A running process substitution, reached by the main command through its
+/// `/dev/fd/N` path.
+pub struct Substitution {
+    file: File,
+    children: Vec<Child>,
+    nested: Vec<Substitution>,
+}
+
+impl Substitution {
+    /// `file` is the shell's end of the pipe connecting the main command to
+    /// the helper processes in `children`. It stays close-on-exec, so only
+    /// the main command, which calls `keep_open` on it, inherits it.
+    pub fn new(file: File, children: Vec<Child>, nested: Vec<Substitution>) -> Substitution {
+        Substitution {
+            file: file,
+            children: children,
+            nested: nested,
+        }
+    }
+
+    pub fn fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+
+    pub fn path(&self) -> String {
+        format!("/dev/fd/{}", self.fd())
+    }
+
+    /// Closes the shell's end of the pipe, so that helpers reading from it see
+    /// the end of their input and helpers writing to it stop, and waits for them.
+    pub fn finish(self) -> Result<()> {
+        drop(self.file);
+
+        for mut child in self.children {
+            child.wait()?;
+        }
+
+        for substitution in self.nested {
+            substitution.finish()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Clears the close-on-exec flag of `fd`, so that the program about to be
+/// executed inherits it. Only meant to be called between `fork` and `exec`.
+pub fn keep_open(fd: RawFd) -> io::Result<()> {
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Creates a pipe, returning its read and write ends.
+pub fn pipe() -> Result<(File, File)> {
+    let mut fds = [0; 2];
+
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(Error::IoError(io::Error::last_os_error()));
+    }
+
+    Ok(unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) })
+}
+
+/// The read end of a pipe that yields `bytes`, written from another thread.
+pub fn pipe_from(bytes: Vec<u8>) -> Result<File> {
+    let (read, mut write) = pipe()?;
+
+    thread::spawn(move || write.write_all(&bytes));
+
+    Ok(read)
+}
@@ -0,0 +1,77 @@
+use readliner::is_complete_lisp;
+use shell::heredoc;
+
+/// Splits a script into the inputs the engine runs one at a time: lisp forms,
+/// which may span several lines, and shell lines together with the bodies of
+/// their here-documents.
+///
+/// Blank lines, `;` comments and `#` comments (including a shebang) are
+/// skipped.
+pub fn split(script: &str) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+
+    for line in script.lines() {
+        if !chunk.is_empty() {
+            chunk.push('\n');
+            chunk.push_str(line);
+
+            if is_complete(&chunk) {
+                chunks.push(chunk.clone());
+                chunk.clear();
+            }
+            continue;
+        }
+
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if is_complete(line) {
+            chunks.push(line.to_owned());
+        } else {
+            chunk.push_str(line);
+        }
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+fn is_complete(chunk: &str) -> bool {
+    if chunk.starts_with('(') {
+        is_complete_lisp(chunk)
+    } else {
+        heredoc::is_complete(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_shell_lines() {
+        assert_eq!(split("ls\n\necho a b\n"), vec!["ls", "echo a b"]);
+    }
+
+    #[test]
+    fn split_multiline_lisp_forms() {
+        assert_eq!(split("(def a\n  (+ 1 2))\necho a\n(a)"), vec!["(def a\n  (+ 1 2))", "echo a", "(a)"]);
+    }
+
+    #[test]
+    fn split_here_documents() {
+        assert_eq!(split("cat <<EOF\n\n  a\nEOF\npwd"), vec!["cat <<EOF\n\n  a\nEOF", "pwd"]);
+    }
+
+    #[test]
+    fn split_skips_comments() {
+        assert_eq!(split("#!/usr/bin/env lish\n; comment\n# comment\npwd"), vec!["pwd"]);
+    }
+}
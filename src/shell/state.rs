@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use env::{Env, env_set};
+use error::{Error, Result};
+use shell::word::MAX_NESTING;
+use types;
+
+/// Shell state that outlives a single command line.
+pub struct State {
+    /// Shared with the `dir-stack` native, which reads it from lisp.
+    pub dir_stack: Rc<RefCell<Vec<PathBuf>>>,
+    /// Exit status of the last pipeline, expanded by `$?`.
+    pub last_status: i32,
+    /// Variables set by `NAME=value` on its own, which aren't exported to
+    /// the commands the shell runs.
+    pub vars: HashMap<String, String>,
+    /// Whether errors of commands are reported as JSON objects.
+    pub json_errors: bool,
+    /// Substitutions running inside each other.
+    nesting: usize,
+}
+
+impl State {
+    pub fn new() -> State {
+        State {
+            dir_stack: Rc::new(RefCell::new(vec![])),
+            last_status: 0,
+            vars: HashMap::new(),
+            json_errors: false,
+            nesting: 0,
+        }
+    }
+
+    /// Value of `$name`, from the shell variables or the environment.
+    pub fn var(&self, name: &str) -> Option<String> {
+        match self.vars.get(name) {
+            Some(value) => Some(value.clone()),
+            None => env::var(name).ok(),
+        }
+    }
+
+    /// Sets a shell variable, or updates the environment when `name` is
+    /// already exported.
+    pub fn set_var(&mut self, name: String, value: String) {
+        if env::var_os(&name).is_some() {
+            env::set_var(name, value);
+        } else {
+            self.vars.insert(name, value);
+        }
+    }
+
+    /// Runs the substitution `f` inside the ones already running, failing
+    /// instead when that would nest them more than `MAX_NESTING` deep.
+    pub fn nested<T, F>(&mut self, f: F) -> Result<T> where F: FnOnce(&mut State) -> Result<T> {
+        if self.nesting >= MAX_NESTING {
+            return Err(Error::DepthExceeded);
+        }
+
+        self.nesting += 1;
+        let result = f(self);
+        self.nesting -= 1;
+
+        result
+    }
+
+    /// The current directory followed by the directory stack, most recent first.
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        dirs(&self.dir_stack.borrow())
+    }
+
+    /// Binds the natives that read the shell state, like `(dir-stack)`.
+    pub fn bind(&self, env: &Env) {
+        let dir_stack = self.dir_stack.clone();
+
+        env_set(env, "dir-stack", types::native_function(move |args, _| {
+            types::Arity::Exactly(0).check("dir-stack", args.len())?;
+
+            let dirs = dirs(&dir_stack.borrow()).into_iter()
+                .map(|dir| types::string(dir.to_string_lossy().into_owned()))
+                .collect();
+
+            Ok(types::list(dirs))
+        }));
+    }
+}
+
+fn dirs(dir_stack: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::current_dir().into_iter().collect();
+
+    dirs.extend(dir_stack.iter().rev().cloned());
+
+    dirs
+}
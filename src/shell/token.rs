@@ -1,4 +1,15 @@
 #[derive(PartialEq, Debug, Clone)]
 pub enum Token {
     Ident(String),
+    Semicolon,
+    And,
+    Pipe,
+    RedirectInput,
+    RedirectOutput,
+    RedirectAppend,
+    RedirectHereDoc,
+    RedirectHereDocStrip,
+    RedirectHereString,
+    /// Body of a here-document, attached once the following lines are read.
+    HereDoc { body: String, expand: bool },
 }
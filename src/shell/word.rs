@@ -0,0 +1,528 @@
+use std::mem;
+
+use error::Result;
+
+/// Something in a word that is replaced by text when the command runs.
+#[derive(PartialEq, Debug)]
+pub enum Expansion<'a> {
+    /// `$(command)` or `` `command` ``
+    Command(&'a str),
+    /// `$NAME` or `${NAME}`
+    Variable(&'a str),
+    /// `$?`
+    Status,
+    /// `<(command)`, replaced by a path to read the command's output from
+    ProcessInput(&'a str),
+    /// `>(command)`, replaced by a path to write the command's input to
+    ProcessOutput(&'a str),
+}
+
+/// Deepest nesting of command and process substitutions. Deeper ones are
+/// treated as unterminated when reading a word and fail when run, since both
+/// recurse.
+pub const MAX_NESTING: usize = 32;
+
+/// Byte length of the shell word at the start of `input`.
+///
+/// A word is made of plain characters, backslash escapes, quoted strings and
+/// expansions, where command and process substitutions may nest and contain
+/// spaces.
+pub fn word_length(input: &str) -> usize {
+    let mut index = 0;
+
+    while let Some(length) = part_length(&input[index..]) {
+        index += length;
+    }
+
+    index
+}
+
+/// Byte length of the part of a word at the start of `input`: a character, a
+/// backslash escape, a quoted string or an expansion.
+pub fn part_length(input: &str) -> Option<usize> {
+    let c = input.chars().next()?;
+    let rest = &input[c.len_utf8()..];
+
+    match c {
+        '\'' => rest.find('\'').map(|end| end + 2),
+        '"' => double_quote_end(rest, 0).map(|end| end + 2),
+        '`' => backtick_end(rest).map(|end| end + 2),
+        '$' => Some(dollar_length(rest, 0) + 1),
+        '<' | '>' if rest.starts_with('(') => paren_end(&rest[1..], 0).map(|end| end + 3),
+        '\\' => rest.chars().next().map(|next| next.len_utf8() + 1),
+        c if is_word_char(c) => Some(c.len_utf8()),
+        _ => None,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "_/-+*%=.{},".contains(c)
+}
+
+/// Index of the `"` closing a double quoted string whose body starts `input`,
+/// inside `nesting` substitutions.
+///
+/// A substitution in the string that doesn't end, or nests too deeply, leaves
+/// the string unterminated too, as it does the substitutions around it.
+fn double_quote_end(input: &str, nesting: usize) -> Option<usize> {
+    let mut index = 0;
+
+    while let Some(c) = input[index..].chars().next() {
+        let rest = &input[index + c.len_utf8()..];
+
+        index += match c {
+            '"' => return Some(index),
+            '\\' => rest.chars().next().map(|next| next.len_utf8() + 1)?,
+            '`' => backtick_end(rest)? + 2,
+            '$' if rest.starts_with('(') => parse_dollar(rest, nesting)?.1 + 1,
+            '$' => dollar_length(rest, nesting) + 1,
+            _ => c.len_utf8(),
+        };
+    }
+
+    None
+}
+
+/// Index of the backtick closing a command substitution whose body starts `input`.
+fn backtick_end(input: &str) -> Option<usize> {
+    let mut index = 0;
+
+    while let Some(c) = input[index..].chars().next() {
+        let rest = &input[index + c.len_utf8()..];
+
+        index += match c {
+            '`' => return Some(index),
+            '\\' => rest.chars().next().map(|next| next.len_utf8() + 1)?,
+            _ => c.len_utf8(),
+        };
+    }
+
+    None
+}
+
+/// Index of the `)` closing a command substitution whose body starts `input`,
+/// inside `nesting` other substitutions.
+fn paren_end(input: &str, nesting: usize) -> Option<usize> {
+    if nesting >= MAX_NESTING {
+        return None;
+    }
+
+    let mut index = 0;
+    let mut depth = 0;
+
+    while let Some(c) = input[index..].chars().next() {
+        let rest = &input[index + c.len_utf8()..];
+
+        index += match c {
+            ')' if depth == 0 => return Some(index),
+            ')' => {
+                depth -= 1;
+                1
+            },
+            '(' => {
+                depth += 1;
+                1
+            },
+            '\'' => rest.find('\'')? + 2,
+            '"' => double_quote_end(rest, nesting + 1)? + 2,
+            '`' => backtick_end(rest)? + 2,
+            '$' if rest.starts_with('(') => parse_dollar(rest, nesting + 1)?.1 + 1,
+            '$' => dollar_length(rest, nesting + 1) + 1,
+            '\\' => rest.chars().next().map(|next| next.len_utf8() + 1)?,
+            _ => c.len_utf8(),
+        };
+    }
+
+    None
+}
+
+/// Byte length of what follows a `$` and belongs to its expansion.
+fn dollar_length(input: &str, nesting: usize) -> usize {
+    match parse_dollar(input, nesting) {
+        Some((_, length)) => length,
+        None => 0,
+    }
+}
+
+/// The expansion introduced by a `$` followed by `input`, with its length.
+fn parse_dollar<'a>(input: &'a str, nesting: usize) -> Option<(Expansion<'a>, usize)> {
+    match input.chars().next()? {
+        '(' => {
+            let end = paren_end(&input[1..], nesting)?;
+            Some((Expansion::Command(&input[1..end + 1]), end + 2))
+        },
+        '{' => {
+            let end = input[1..].find(|c: char| c != '?' && c != '_' && !c.is_ascii_alphanumeric())? + 1;
+            if !input[end..].starts_with('}') {
+                return None;
+            }
+
+            match &input[1..end] {
+                "?" => Some((Expansion::Status, end + 1)),
+                name if is_name(name) => Some((Expansion::Variable(name), end + 1)),
+                _ => None,
+            }
+        },
+        '?' => Some((Expansion::Status, 1)),
+        c if c == '_' || c.is_ascii_alphabetic() => {
+            let length = input.find(|c: char| c != '_' && !c.is_ascii_alphanumeric()).unwrap_or(input.len());
+            Some((Expansion::Variable(&input[..length]), length))
+        },
+        _ => None,
+    }
+}
+
+/// Fields produced by expanding a word.
+struct Fields {
+    fields: Vec<String>,
+    current: String,
+    started: bool,
+    split: bool,
+}
+
+impl Fields {
+    fn push(&mut self, c: char) {
+        self.current.push(c);
+        self.started = true;
+    }
+
+    /// Adds the result of an expansion, splitting it on whitespace unless quoted.
+    fn push_expanded(&mut self, text: &str, quoted: bool) {
+        if quoted || !self.split {
+            self.current.push_str(text);
+            self.started = true;
+            return;
+        }
+
+        for c in text.chars() {
+            if c == ' ' || c == '\t' || c == '\n' {
+                if self.started {
+                    self.fields.push(mem::replace(&mut self.current, String::new()));
+                    self.started = false;
+                }
+            } else {
+                self.push(c);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if self.started {
+            self.fields.push(self.current);
+        }
+
+        self.fields
+    }
+}
+
+/// Expands a word into fields, removing its quotes.
+///
+/// `expander` provides the text of every expansion. Unquoted expansions are
+/// split on whitespace, so a word can produce zero or several fields.
+/// Trailing newlines are removed from the output of command substitutions.
+pub fn expand<F>(word: &str, expander: &mut F) -> Result<Vec<String>> where F: FnMut(&Expansion) -> Result<String> {
+    expand_fields(word, true, expander)
+}
+
+/// Expands a word into a single string, without field splitting.
+pub fn expand_single<F>(word: &str, expander: &mut F) -> Result<String> where F: FnMut(&Expansion) -> Result<String> {
+    Ok(expand_fields(word, false, expander)?.pop().unwrap_or_default())
+}
+
+fn expand_fields<F>(word: &str, split: bool, expander: &mut F) -> Result<Vec<String>> where F: FnMut(&Expansion) -> Result<String> {
+    let mut fields = Fields {
+        fields: vec![],
+        current: String::new(),
+        started: false,
+        split: split,
+    };
+    let mut index = 0;
+
+    while let Some(c) = word[index..].chars().next() {
+        let rest = &word[index + c.len_utf8()..];
+        index += c.len_utf8();
+
+        match c {
+            '\'' => {
+                let end = rest.find('\'').unwrap_or(rest.len());
+                fields.push_expanded(&rest[..end], true);
+                index += (end + 1).min(rest.len());
+            },
+            '"' => {
+                let end = double_quote_end(rest, 0).unwrap_or(rest.len());
+                fields.push_expanded("", true);
+                expand_quoted(&rest[..end], "\"\\$`", &mut fields, expander)?;
+                index += (end + 1).min(rest.len());
+            },
+            '`' => {
+                let end = backtick_end(rest).unwrap_or(rest.len());
+                let text = substitute(&rest[..end], expander)?;
+                fields.push_expanded(&text, false);
+                index += (end + 1).min(rest.len());
+            },
+            '$' => {
+                match parse_dollar(rest, 0) {
+                    Some((expansion, length)) => {
+                        let text = run_expansion(&expansion, expander)?;
+                        fields.push_expanded(&text, false);
+                        index += length;
+                    },
+                    None => fields.push(c),
+                }
+            },
+            '<' | '>' if rest.starts_with('(') => {
+                let end = paren_end(&rest[1..], 0).unwrap_or(rest.len() - 1);
+                let command = &rest[1..end + 1];
+
+                let expansion = if c == '<' {
+                    Expansion::ProcessInput(command)
+                } else {
+                    Expansion::ProcessOutput(command)
+                };
+
+                let path = run_expansion(&expansion, expander)?;
+                fields.push_expanded(&path, true);
+                index += (end + 2).min(rest.len());
+            },
+            '\\' => {
+                if let Some(next) = rest.chars().next() {
+                    fields.push(next);
+                    index += next.len_utf8();
+                }
+            },
+            _ => fields.push(c),
+        }
+    }
+
+    Ok(fields.finish())
+}
+
+/// Expands the body of a here-document, where quotes have no special meaning.
+pub fn expand_here_doc<F>(body: &str, expander: &mut F) -> Result<String> where F: FnMut(&Expansion) -> Result<String> {
+    let mut fields = Fields {
+        fields: vec![],
+        current: String::new(),
+        started: false,
+        split: false,
+    };
+
+    expand_quoted(body, "\\$`", &mut fields, expander)?;
+
+    Ok(fields.current)
+}
+
+/// Expands text where a backslash only escapes the characters in `escapable`
+/// and is kept before any other, as inside double quotes.
+fn expand_quoted<F>(body: &str, escapable: &str, fields: &mut Fields, expander: &mut F) -> Result<()> where F: FnMut(&Expansion) -> Result<String> {
+    let mut index = 0;
+
+    while let Some(c) = body[index..].chars().next() {
+        let rest = &body[index + c.len_utf8()..];
+        index += c.len_utf8();
+
+        match c {
+            '\\' => {
+                match rest.chars().next() {
+                    Some(next) if escapable.contains(next) => {
+                        fields.push(next);
+                        index += 1;
+                    },
+                    _ => fields.push(c),
+                }
+            },
+            '`' => {
+                let end = backtick_end(rest).unwrap_or(rest.len());
+                let text = substitute(&rest[..end], expander)?;
+                fields.push_expanded(&text, true);
+                index += (end + 1).min(rest.len());
+            },
+            '$' => {
+                match parse_dollar(rest, 0) {
+                    Some((expansion, length)) => {
+                        let text = run_expansion(&expansion, expander)?;
+                        fields.push_expanded(&text, true);
+                        index += length;
+                    },
+                    None => fields.push(c),
+                }
+            },
+            _ => fields.push(c),
+        }
+    }
+
+    Ok(())
+}
+
+fn substitute<F>(command: &str, expander: &mut F) -> Result<String> where F: FnMut(&Expansion) -> Result<String> {
+    run_expansion(&Expansion::Command(command), expander)
+}
+
+fn run_expansion<F>(expansion: &Expansion, expander: &mut F) -> Result<String> where F: FnMut(&Expansion) -> Result<String> {
+    let mut text = expander(expansion)?;
+
+    if let Expansion::Command(_) = *expansion {
+        let length = text.trim_end_matches('\n').len();
+        text.truncate(length);
+    }
+
+    Ok(text)
+}
+
+/// Whether `name` can be used as an environment variable name.
+pub fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => {},
+        _ => return false,
+    }
+
+    chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// Splits a `NAME=value` word into its name and its still quoted value.
+pub fn split_assignment(word: &str) -> Option<(&str, &str)> {
+    let index = word.find('=')?;
+    let (name, value) = (&word[..index], &word[index + 1..]);
+
+    if is_name(name) {
+        Some((name, value))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use super::*;
+
+    fn unquote(word: &str) -> String {
+        expand_single(word, &mut |_: &Expansion| Err(Error::ParseError)).unwrap()
+    }
+
+    /// Expands commands to their text followed by newlines, variables to
+    /// their names in upper case and the status to 1.
+    fn expand_str(word: &str) -> Vec<String> {
+        expand(word, &mut |expansion: &Expansion| {
+            match *expansion {
+                Expansion::Command(command) => Ok(format!("{}\n\n", command)),
+                Expansion::Variable(name) => Ok(name.to_uppercase()),
+                Expansion::Status => Ok("1".to_owned()),
+                Expansion::ProcessInput(command) => Ok(format!("/in/{}", command)),
+                Expansion::ProcessOutput(command) => Ok(format!("/out/{}", command)),
+            }
+        }).unwrap()
+    }
+
+    #[test]
+    fn unquote_plain_word() {
+        assert_eq!(unquote("ls"), "ls");
+    }
+
+    #[test]
+    fn unquote_double_quotes() {
+        assert_eq!(unquote("\"a b\""), "a b");
+        assert_eq!(unquote("\"a \\\" \\n\""), "a \" \\n");
+    }
+
+    #[test]
+    fn unquote_single_quotes() {
+        assert_eq!(unquote("'a \"b\" \\n'"), "a \"b\" \\n");
+    }
+
+    #[test]
+    fn unquote_mixed_parts() {
+        assert_eq!(unquote("ll='ls -la'"), "ll=ls -la");
+        assert_eq!(unquote("a\"b c\"'d'"), "ab cd");
+    }
+
+    #[test]
+    fn word_lengths() {
+        assert_eq!(word_length("ls -la"), 2);
+        assert_eq!(word_length("$(echo a b) c"), 11);
+        assert_eq!(word_length("a$(b $(c) \")\")d e"), 15);
+        assert_eq!(word_length("`echo a` b"), 8);
+        assert_eq!(word_length("\"$(echo \"a b\")\" c"), 15);
+        assert_eq!(word_length("a\\ b c"), 4);
+        assert_eq!(word_length("$(unterminated"), 1);
+        assert_eq!(word_length("a|b"), 1);
+        assert_eq!(word_length("<(sort a | uniq) b"), 16);
+        assert_eq!(word_length("--in=>(cat) b"), 11);
+        assert_eq!(word_length("<in"), 0);
+    }
+
+    #[test]
+    fn word_lengths_of_deep_nesting() {
+        let nested = format!("{}{}", "$(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        assert_eq!(word_length(&nested), nested.len());
+
+        let too_deep = format!("a{}{}", "$(".repeat(MAX_NESTING + 1), ")".repeat(MAX_NESTING + 1));
+        assert_eq!(word_length(&too_deep), 2);
+
+        assert_eq!(word_length(&"$(".repeat(100_000)), 1);
+        assert_eq!(word_length(&"\"$(".repeat(100_000)), 0);
+        let unterminated = "${".repeat(100_000);
+        assert_eq!(word_length(&unterminated), unterminated.len());
+    }
+
+    #[test]
+    fn expand_command_substitution() {
+        assert_eq!(expand_str("$(a b)"), vec!["a", "b"]);
+        assert_eq!(expand_str("x$(a b)y"), vec!["xa", "by"]);
+        assert_eq!(expand_str("\"$(a b)\""), vec!["a b"]);
+        assert_eq!(expand_str("`a b`"), vec!["a", "b"]);
+        assert_eq!(expand_str("\"`a b`\""), vec!["a b"]);
+        assert_eq!(expand_str("$(a $(b) c)"), vec!["a", "$(b)", "c"]);
+    }
+
+    #[test]
+    fn expand_process_substitution() {
+        assert_eq!(expand_str("<(a b)"), vec!["/in/a b"]);
+        assert_eq!(expand_str("--log=>(c)"), vec!["--log=/out/c"]);
+        assert_eq!(expand_str("\"<(a)\""), vec!["<(a)"]);
+    }
+
+    #[test]
+    fn expand_to_no_fields() {
+        assert_eq!(expand_str("$()"), Vec::<String>::new());
+        assert_eq!(expand_str("\"$()\""), vec![""]);
+        assert_eq!(expand_str("''"), vec![""]);
+    }
+
+    #[test]
+    fn expand_variables() {
+        assert_eq!(expand_str("$home/${dir}x"), vec!["HOME/DIRx"]);
+        assert_eq!(expand_str("$?"), vec!["1"]);
+        assert_eq!(expand_str("\"$?\""), vec!["1"]);
+        assert_eq!(expand_str("'$home'"), vec!["$home"]);
+        assert_eq!(expand_str("\\$home"), vec!["$home"]);
+        assert_eq!(expand_str("a$"), vec!["a$"]);
+        assert_eq!(expand_str("$1"), vec!["$1"]);
+        assert_eq!(expand_str("${a}b}"), vec!["Ab}"]);
+    }
+
+    #[test]
+    fn expand_here_doc_keeps_quotes() {
+        let text = expand_here_doc("\"$a\" '\\$a' \\\"\n", &mut |_: &Expansion| Ok("x".to_owned())).unwrap();
+
+        assert_eq!(text, "\"x\" '$a' \\\"\n");
+    }
+
+    #[test]
+    fn expand_single_does_not_split() {
+        let text = expand_single("$(a b)", &mut |_: &Expansion| Ok("a  b\n".to_owned())).unwrap();
+
+        assert_eq!(text, "a  b");
+    }
+
+    #[test]
+    fn split_assignments() {
+        assert_eq!(split_assignment("FOO=bar"), Some(("FOO", "bar")));
+        assert_eq!(split_assignment("_A1='a b'"), Some(("_A1", "'a b'")));
+        assert_eq!(split_assignment("FOO="), Some(("FOO", "")));
+        assert_eq!(split_assignment("1A=b"), None);
+        assert_eq!(split_assignment("\"A\"=b"), None);
+        assert_eq!(split_assignment("--opt"), None);
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use error::Result;
+
+/// Modules of the standard library, in the order they are loaded.
+pub const MODULES: &[(&str, &str)] = &[
+    ("file", include_str!("../stdlib/file.lish")),
+    ("list", include_str!("../stdlib/list.lish")),
+];
+
+/// Directory set in `LISH_STDLIB_DIR`, with modules to load instead of the
+/// embedded ones.
+pub fn override_dir() -> Option<PathBuf> {
+    match env::var("LISH_STDLIB_DIR") {
+        Ok(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => None,
+    }
+}
+
+/// Source of module `name`, from `name.lish` in `dir` when it is there.
+pub fn source(name: &str, embedded: &str, dir: Option<&Path>) -> Result<String> {
+    let path = match dir {
+        Some(dir) => dir.join(format!("{}.lish", name)),
+        None => return Ok(embedded.to_owned()),
+    };
+
+    if !path.is_file() {
+        return Ok(embedded.to_owned());
+    }
+
+    let mut buffer = String::new();
+    File::open(path)?.read_to_string(&mut buffer)?;
+
+    Ok(buffer)
+}
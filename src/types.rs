@@ -1,44 +1,191 @@
 use std;
 use std::rc::Rc;
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::collections::HashMap;
 
-use error::Result;
+use error::{Error, Result};
 use env::Env;
+use lisp::evaluator::Evaluator;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum RedirectKind {
+    Input,
+    Output,
+    Append,
+    /// `<<EOF`, the target is the body, expanded unless the delimiter was quoted
+    HereDoc { expand: bool },
+    /// `<<<`, the target is a word fed to the command followed by a newline
+    HereString,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: String,
+}
+
+/// A `NAME=value` word in front of a command.
+#[derive(PartialEq, Debug)]
+pub struct Assignment {
+    pub name: String,
+    pub value: String,
+}
 
 #[derive(PartialEq, Debug)]
 pub struct ShellExpr {
+    pub assignments: Vec<Assignment>,
     pub words: Vec<String>,
+    pub redirects: Vec<Redirect>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Pipeline {
+    pub commands: Vec<ShellExpr>,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Connector {
+    Always,
+    And,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ShellList {
+    pub items: Vec<(Connector, Pipeline)>,
 }
 
 #[derive(PartialEq, Debug)]
 pub enum LispType {
     Nil,
+    Bool(bool),
     Integer(i64),
     Symbol(String),
+    Keyword(String),
     Strn(String),
+    Char(char),
     Function(FunctionData),
     NativeFunction(NativeFunctionType),
     List(Vec<LispValue>),
-    HashMap(HashMap<String, LispValue>),
+    Vector(Vec<LispValue>),
+    HashMap(HashMap<HashKey, LispValue>),
+    Atom(AtomType),
 }
 
 pub type LispValue = Rc<LispType>;
 
+/// The values that can be used as hash map keys.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone)]
+pub enum HashKey {
+    Integer(i64),
+    Char(char),
+    Keyword(String),
+    Strn(String),
+}
+
+impl HashKey {
+    pub fn from_value(value: &LispValue) -> Result<HashKey> {
+        match **value {
+            LispType::Integer(i) => Ok(HashKey::Integer(i)),
+            LispType::Char(c) => Ok(HashKey::Char(c)),
+            LispType::Keyword(ref name) => Ok(HashKey::Keyword(name.to_owned())),
+            LispType::Strn(ref s) => Ok(HashKey::Strn(s.to_owned())),
+            _ => Err(Error::TypeError),
+        }
+    }
+
+    pub fn to_value(&self) -> LispValue {
+        match *self {
+            HashKey::Integer(i) => integer(i),
+            HashKey::Char(c) => character(c),
+            HashKey::Keyword(ref name) => keyword(name.to_owned()),
+            HashKey::Strn(ref s) => string(s.to_owned()),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for HashKey {
+    fn from(value: &'a str) -> HashKey {
+        HashKey::Strn(value.to_owned())
+    }
+}
+
+impl From<String> for HashKey {
+    fn from(value: String) -> HashKey {
+        HashKey::Strn(value)
+    }
+}
+
+impl From<i64> for HashKey {
+    fn from(value: i64) -> HashKey {
+        HashKey::Integer(value)
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct FunctionData {
+    /// Name the function was first bound to with `def`, for error messages.
+    pub name: Option<String>,
     pub params: Vec<String>,
     pub body: LispValue,
     pub env: Env,
 }
 
+impl FunctionData {
+    /// The name errors refer to the function by: its name or else its
+    /// parameter list, like `(fn (a b))`.
+    pub fn display_name(&self) -> String {
+        match self.name {
+            Some(ref name) => name.to_owned(),
+            None => format!("(fn ({}))", self.params.join(" ")),
+        }
+    }
+
+    /// A copy of the function with `name`.
+    pub fn named(&self, name: &str) -> LispValue {
+        self.copy(Some(name.to_owned()), self.env.clone())
+    }
+
+    /// A copy of the function closing over `env` instead.
+    pub fn with_env(&self, env: Env) -> LispValue {
+        self.copy(self.name.clone(), env)
+    }
+
+    fn copy(&self, name: Option<String>, env: Env) -> LispValue {
+        Rc::new(LispType::Function(FunctionData {
+            name: name,
+            params: self.params.clone(),
+            body: self.body.clone(),
+            env: env,
+        }))
+    }
+}
+
+/// What a native function can use besides its arguments.
+pub struct CallContext<'a> {
+    pub evaluator: &'a Evaluator,
+    /// Environment the function is called from.
+    pub env: &'a Env,
+}
+
+impl<'a> CallContext<'a> {
+    /// Calls a lisp or native function, as passed to `map` or `swap!`.
+    pub fn apply(&self, function: &LispValue, args: &[LispValue]) -> Result<LispValue> {
+        self.evaluator.apply_function(function.clone(), args, self.env)
+    }
+}
+
+pub type NativeFunctionBody = Rc<dyn Fn(&[LispValue], &CallContext) -> Result<LispValue>>;
+
+/// A function implemented in Rust, which may be a closure capturing state.
 pub struct NativeFunctionType {
-    pub body: fn(&[LispValue]) -> Result<LispValue>,
+    pub body: NativeFunctionBody,
 }
 
+/// Native functions are only equal to themselves.
 impl PartialEq for NativeFunctionType {
     fn eq(&self, other: &NativeFunctionType) -> bool {
-        (self.body as *const fn(&[LispValue]) -> Result<LispValue>) == (other.body as *const  fn(&[LispValue]) -> Result<LispValue>)
+        Rc::ptr_eq(&self.body, &other.body)
     }
 }
 
@@ -48,20 +195,59 @@ impl Debug for NativeFunctionType {
     }
 }
 
+/// A mutable reference to a value, changed with `reset!` and `swap!`.
+pub struct AtomType {
+    pub value: RefCell<LispValue>,
+}
+
+/// Atoms are only equal to themselves, which also keeps comparing an atom
+/// that contains itself from recursing forever.
+impl PartialEq for AtomType {
+    fn eq(&self, other: &AtomType) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for AtomType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "#<atom ...>")
+    }
+}
+
+/// Characters written by name, like `\space`, instead of as themselves.
+pub const CHAR_NAMES: &[(&str, char)] = &[
+    ("space", ' '),
+    ("newline", '\n'),
+    ("tab", '\t'),
+    ("return", '\r'),
+];
+
 // Constructors
 
 pub fn nil() -> LispValue {
     Rc::new(LispType::Nil)
 }
 
+pub fn boolean(value: bool) -> LispValue {
+    Rc::new(LispType::Bool(value))
+}
+
 pub fn list(seq: Vec<LispValue>) -> LispValue {
     Rc::new(LispType::List(seq))
 }
 
-pub fn hash_map(data: HashMap<String, LispValue>) -> LispValue {
+pub fn vector(seq: Vec<LispValue>) -> LispValue {
+    Rc::new(LispType::Vector(seq))
+}
+
+pub fn hash_map(data: HashMap<HashKey, LispValue>) -> LispValue {
     Rc::new(LispType::HashMap(data))
 }
 
+pub fn atom(value: LispValue) -> LispValue {
+    Rc::new(LispType::Atom(AtomType { value: RefCell::new(value) }))
+}
+
 pub fn integer(i: i64) -> LispValue {
     Rc::new(LispType::Integer(i))
 }
@@ -74,14 +260,77 @@ pub fn symbol(value: String) -> LispValue {
     Rc::new(LispType::Symbol(value))
 }
 
-pub fn native_function(f: fn(&[LispValue]) -> Result<LispValue>) -> LispValue {
-    Rc::new(LispType::NativeFunction(NativeFunctionType{ body: f }))
+pub fn keyword(name: String) -> LispValue {
+    Rc::new(LispType::Keyword(name))
+}
+
+pub fn character(value: char) -> LispValue {
+    Rc::new(LispType::Char(value))
+}
+
+/// Number of arguments a function takes.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+    Between(usize, usize),
+}
+
+impl Arity {
+    /// Fails with an arity error naming `name` when `count` arguments don't fit.
+    pub fn check(&self, name: &str, count: usize) -> Result<()> {
+        let fits = match *self {
+            Arity::Exactly(n) => count == n,
+            Arity::AtLeast(min) => count >= min,
+            Arity::Between(min, max) => count >= min && count <= max,
+        };
+
+        if fits {
+            Ok(())
+        } else {
+            Err(Error::ArityError(name.to_owned(), *self, count))
+        }
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Arity::Exactly(1) => write!(f, "1 argument"),
+            Arity::Exactly(n) => write!(f, "{} arguments", n),
+            Arity::AtLeast(1) => write!(f, "at least 1 argument"),
+            Arity::AtLeast(n) => write!(f, "at least {} arguments", n),
+            Arity::Between(min, max) => write!(f, "{} to {} arguments", min, max),
+        }
+    }
+}
+
+pub fn native_function<F>(f: F) -> LispValue where F: Fn(&[LispValue], &CallContext) -> Result<LispValue> + 'static {
+    Rc::new(LispType::NativeFunction(NativeFunctionType{ body: Rc::new(f) }))
 }
 
 pub fn function(params: Vec<String>, body: LispValue, env: Env) -> LispValue {
     Rc::new(LispType::Function(FunctionData {
+        name: None,
         params: params,
         body: body,
         env: env,
     }))
 }
+
+/// Everything except nil and false counts as true.
+pub fn is_truthy(value: &LispValue) -> bool {
+    match **value {
+        LispType::Nil | LispType::Bool(false) => false,
+        _ => true,
+    }
+}
+
+/// Entries of a hash map ordered by key, so that they always print the same.
+pub fn sorted_entries(data: &HashMap<HashKey, LispValue>) -> Vec<(&HashKey, &LispValue)> {
+    let mut entries: Vec<_> = data.iter().collect();
+
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    entries
+}
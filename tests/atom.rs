@@ -0,0 +1,78 @@
+extern crate lish;
+
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+#[test]
+fn deref_atom() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def a (atom 1))");
+
+    assert_eq!(run(&mut engine, "(deref a)"), "1");
+    assert_eq!(run(&mut engine, "(list @a)"), "(1)");
+    assert_eq!(run(&mut engine, "(do a)"), "#<atom 1>");
+}
+
+#[test]
+fn reset_atom() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def a (atom 1))");
+
+    assert_eq!(run(&mut engine, "(reset! a \"b\")"), "\"b\"");
+    assert_eq!(run(&mut engine, "(deref a)"), "\"b\"");
+}
+
+#[test]
+fn swap_atom() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def a (atom 1))");
+
+    assert_eq!(run(&mut engine, "(swap! a + 2)"), "3");
+    assert_eq!(run(&mut engine, "(swap! a (fn (x) (+ x @a)))"), "6");
+    assert_eq!(run(&mut engine, "(deref a)"), "6");
+}
+
+#[test]
+fn atoms_are_shared() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def seen (atom []))");
+    run(&mut engine, "(def record (fn (x) (swap! seen (fn (xs) (cons x xs)))))");
+    run(&mut engine, "(record 1)");
+    run(&mut engine, "(record 2)");
+
+    assert_eq!(run(&mut engine, "(deref seen)"), "(2 1)");
+}
+
+#[test]
+fn atom_containing_itself() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def a (atom nil))");
+    run(&mut engine, "(reset! a a)");
+
+    let a = engine.run("(do a)").unwrap().unwrap();
+
+    assert_eq!(run(&mut engine, "(do a)"), "#<atom #<atom ...>>");
+    assert_eq!(a, a.clone());
+    assert!(engine.run("(to-json a)").is_err());
+}
+
+#[test]
+fn atoms_are_only_equal_to_themselves() {
+    let mut engine = Engine::new();
+
+    let a = engine.run("(atom 1)").unwrap().unwrap();
+    let b = engine.run("(atom 1)").unwrap().unwrap();
+
+    assert_ne!(a, b);
+}
@@ -0,0 +1,92 @@
+extern crate lish;
+
+use lish::Error;
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+#[test]
+fn thread_last() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(->> 1)"), "1");
+    assert_eq!(run(&mut engine, "(->> 5 (- 1))"), "-4");
+    assert_eq!(run(&mut engine, "(->> (list 3 1 2) sort (map (fn (x) (+ x x))))"), "(2 4 6)");
+    assert_eq!(run(&mut engine, "(->> (hash :a 1) :a)"), "1");
+}
+
+#[test]
+fn thread_first() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(-> 1)"), "1");
+    assert_eq!(run(&mut engine, "(-> 5 (- 1))"), "4");
+    assert_eq!(run(&mut engine, "(-> (hash :a 1) (get :a) (+ 1))"), "2");
+}
+
+#[test]
+fn thread_directory_names() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(->> (ls-data \"tests/fixtures\") (filter dir?) (map :name))"), "(\"dir1\")");
+
+    // `->` puts the list before the function, where filter can't take it
+    match engine.run("(-> (ls-data \"tests/fixtures\") (filter dir?) (map :name))") {
+        Err(Error::TypeError) => {},
+        result => panic!("expected a type error, got {:?}", result),
+    }
+}
+
+#[test]
+fn thread_syntax_error() {
+    let mut engine = Engine::new();
+
+    for input in &["(->)", "(->>)"] {
+        match engine.run(input) {
+            Err(Error::BadSyntax(..)) => {},
+            _ => panic!("expected a syntax error for {}", input),
+        }
+    }
+}
+
+#[test]
+fn ls_data() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(->> (ls-data \"tests/fixtures\") (map :name))"), "(\"dir1\")");
+    assert_eq!(run(&mut engine, "(->> (ls-data \"tests/fixtures/dir1\") (map :path))"), "(\"tests/fixtures/dir1/file1\")");
+    assert_eq!(run(&mut engine, "(->> (ls-data \"tests/fixtures\") (map keys) first)"),
+               "(:mtime :name :path :permissions :size :type)");
+    assert!(engine.run("(ls-data \"tests/nonexistent\")").is_err());
+}
+
+#[test]
+fn json_round_trip() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(from-json (to-json (hash :a [1 \"b\" nil] :c (hash :d false))))"),
+               "{ :a (1 \"b\" nil) :c { :d false } }");
+    assert!(engine.run("(from-json \"{\")").is_err());
+    assert!(engine.run("(to-json +)").is_err());
+    assert!(engine.run("(to-json {\"a\" 1 :a 2})").is_err());
+}
+
+#[test]
+fn pipe_values_to_commands() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(pipe \"cat\" \"text\")"), "\"text\"");
+    assert_eq!(run(&mut engine, "(->> [3 1 2] (pipe \"sort\" \"-r\") lines)"), "(\"3\" \"2\" \"1\")");
+    assert_eq!(run(&mut engine, "(->> (list (hash :a 1) (hash :a 2)) (pipe \"cat\") lines (map from-json) (map :a))"), "(1 2)");
+    assert!(engine.run("(pipe \"false\" nil)").is_err());
+    assert!(engine.run("(pipe \"grep\" \"x\" \"abc\")").is_err());
+    assert_eq!(run(&mut engine, "(pipe :no-match \"grep\" \"x\" \"abc\")"), "\"\"");
+    assert_eq!(run(&mut engine, "(pipe :no-match \"grep\" \"b\" \"abc\")"), "\"abc\n\"");
+    assert!(engine.run("(pipe :no-match \"sh\" \"-c\" \"exit 2\" nil)").is_err());
+    assert!(engine.run("(pipe :no-match nil)").is_err());
+}
@@ -0,0 +1,125 @@
+extern crate lish;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use lish::engine::Engine;
+use lish::printer::Printer;
+use lish::{Error, FromLisp, IntoLisp, LispValue};
+
+fn print(value: &LispValue) -> String {
+    Printer::new().print(value, true)
+}
+
+#[test]
+fn define_values() {
+    let mut engine = Engine::new();
+
+    engine.define("n", 42);
+    engine.define("name", "lish");
+    engine.define("names", vec!["a".to_owned(), "b".to_owned()]);
+    engine.define("nothing", None::<i64>);
+
+    assert_eq!(print(&engine.eval_str("(list n name names nothing)").unwrap()), "(42 \"lish\" (\"a\" \"b\") nil)");
+}
+
+#[test]
+fn define_hash_map() {
+    let mut engine = Engine::new();
+
+    let mut ports = HashMap::new();
+    ports.insert("http".to_owned(), 80);
+    ports.insert("ssh".to_owned(), 22);
+    engine.define("ports", ports);
+
+    assert_eq!(print(&engine.eval_str("(get ports \"ssh\")").unwrap()), "22");
+}
+
+#[test]
+fn register_fn() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("double", |args| Ok(i64::from_lisp(&args[0])? * 2));
+
+    assert_eq!(print(&engine.eval_str("(double 21)").unwrap()), "42");
+    assert_eq!(print(&engine.eval_str("(map double [1 2])").unwrap()), "(2 4)");
+}
+
+#[test]
+fn register_closure_with_state() {
+    let mut engine = Engine::new();
+    let log = Rc::new(RefCell::new(vec![]));
+
+    let recorded = log.clone();
+    engine.register_fn("record", move |args| {
+        recorded.borrow_mut().push(String::from_lisp(&args[0])?);
+        Ok(())
+    });
+
+    engine.eval_str("(record \"a\") (record \"b\")").unwrap();
+
+    assert_eq!(*log.borrow(), vec!["a", "b"]);
+}
+
+#[test]
+fn register_fn_errors() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("double", |args| Ok(i64::from_lisp(&args[0])? * 2));
+
+    match engine.eval_str("(double \"a\")") {
+        Err(Error::TypeError) => {},
+        other => panic!("expected type error, got {:?}", other),
+    }
+}
+
+#[test]
+fn eval_str_returns_last_value() {
+    let mut engine = Engine::new();
+
+    let value = engine.eval_str("(def a 1) (+ a 2)").unwrap();
+
+    assert_eq!(i64::from_lisp(&value).unwrap(), 3);
+    assert_eq!(print(&engine.eval_str("").unwrap()), "nil");
+}
+
+#[test]
+fn eval_str_rejects_unbalanced_input() {
+    let mut engine = Engine::new();
+
+    assert!(engine.eval_str("1) (def x 2").is_err());
+    assert!(engine.eval_str("(do x)").is_err());
+}
+
+#[test]
+fn call_lisp_function() {
+    let mut engine = Engine::new();
+
+    engine.eval_str("(def greet (fn (name) (string-append \"hello \" name)))").unwrap();
+
+    let value = engine.call("greet", &["lish".into_lisp()]).unwrap();
+
+    assert_eq!(String::from_lisp(&value).unwrap(), "hello lish");
+}
+
+#[test]
+fn call_undefined_function() {
+    let mut engine = Engine::new();
+
+    assert!(engine.call("undefined", &[]).is_err());
+}
+
+#[test]
+fn convert_collections() {
+    let mut engine = Engine::new();
+
+    let numbers: Vec<i64> = Vec::from_lisp(&engine.eval_str("[1 2 3]").unwrap()).unwrap();
+    assert_eq!(numbers, vec![1, 2, 3]);
+
+    let data: HashMap<String, Option<i64>> = HashMap::from_lisp(&engine.eval_str("{\"a\" 1 \"b\" nil}").unwrap()).unwrap();
+    assert_eq!(data.get("a"), Some(&Some(1)));
+    assert_eq!(data.get("b"), Some(&None));
+
+    assert!(Vec::<i64>::from_lisp(&engine.eval_str("[1 \"a\"]").unwrap()).is_err());
+}
@@ -0,0 +1,120 @@
+extern crate lish;
+
+use lish::Error;
+use lish::engine::Engine;
+
+fn error(input: &str) -> Error {
+    let mut engine = Engine::new();
+
+    match engine.run(input) {
+        Err(error) => error,
+        Ok(_) => panic!("expected {} to fail", input),
+    }
+}
+
+#[test]
+fn native_arity_error() {
+    match error("(nth (list 1))") {
+        Error::ArityError(ref name, _, 1) => assert_eq!(name, "nth"),
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    match error("(first)") {
+        Error::ArityError(ref name, _, 0) => assert_eq!(name, "first"),
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn function_arity_error() {
+    match error("((fn (a b) a) 1)") {
+        Error::ArityError(ref name, _, 1) => assert_eq!(name, "(fn (a b))"),
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    match error("(do (def greet (fn (name) name)) (greet))") {
+        Error::ArityError(ref name, _, 0) => assert_eq!(name, "greet"),
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn special_form_syntax_error() {
+    for input in &["(def)", "(def 1 2)", "(def a)", "(set! 1 2)", "(fn)", "(fn a 1)", "(fn (1) 1)", "(eval)", "(export 1)"] {
+        match error(input) {
+            Error::BadSyntax(..) => {},
+            e => panic!("unexpected error for {}: {:?}", input, e),
+        }
+    }
+}
+
+#[test]
+fn integer_overflow() {
+    match error("(+ 9223372036854775807 1)") {
+        Error::IntegerOverflow => {},
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    match error("(do 99999999999999999999)") {
+        Error::IntegerOverflow => {},
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn display_errors() {
+    assert_eq!(error("(do undefined)").to_string(), "lookup error: undefined binding: undefined");
+    assert_eq!(error("(nth (list 1))").to_string(), "arity error: nth: expected 2 arguments, got 1");
+    assert_eq!(error("(1 2)").to_string(), "apply error: expected function, got: 1");
+}
+
+#[test]
+fn error_source() {
+    use std::error::Error as StdError;
+    use std::io;
+
+    let error = Error::LoadError("init.lish".to_owned(), Some(2), Box::new(Error::IoError(io::Error::other("oops"))));
+
+    assert_eq!(error.to_string(), "init.lish:2: io error: oops");
+    assert_eq!(error.source().unwrap().to_string(), "io error: oops");
+    assert_eq!(error.source().unwrap().source().unwrap().to_string(), "oops");
+}
+
+#[test]
+fn error_codes() {
+    let error = error("(do undefined)");
+
+    assert_eq!(error.category(), "lookup");
+    assert_eq!(error.code(), "undefined-binding");
+}
+
+#[test]
+fn catch_error() {
+    let mut engine = Engine::new();
+    let printer = lish::printer::Printer::new();
+
+    let mut run = |input: &str| printer.print(&engine.run(input).unwrap().unwrap(), true);
+
+    assert_eq!(run("(try (+ 1 2) (catch e 0))"), "3");
+    assert_eq!(run("(try (do undefined) (catch e (:name e)))"), "\"undefined\"");
+    assert_eq!(run("(try (first) (catch e (:category e) (:code e)))"), "\"wrong-arity\"");
+    assert_eq!(run("(try (nth (list) 1) (catch e e))"), "{ :category \"index\" :code \"out-of-range\" :index 1 :message \"index error: index out of range: 1\" }");
+}
+
+#[test]
+fn try_syntax_error() {
+    for input in &["(try)", "(try 1)", "(try 1 (finally e 2))", "(try 1 (catch 2 3))"] {
+        match error(input) {
+            Error::BadSyntax(..) => {},
+            e => panic!("unexpected error for {}: {:?}", input, e),
+        }
+    }
+}
+
+#[test]
+fn huge_range() {
+    match error("(range 9223372036854775807)") {
+        Error::IndexOutOfRange(_) => {},
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
@@ -52,3 +52,22 @@ fn argument_and_do() {
 
     assert_eq!(run(&mut engine, "((fn (a) (do (+ a 1))) 3)"), "4");
 }
+
+#[test]
+fn set_changes_captured_binding() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def counter 0)");
+    run(&mut engine, "(def increment (fn () (set! counter (+ counter 1))))");
+    run(&mut engine, "(increment)");
+    run(&mut engine, "(increment)");
+
+    assert_eq!(run(&mut engine, "(do counter)"), "2");
+}
+
+#[test]
+fn set_undefined_binding() {
+    let mut engine = Engine::new();
+
+    assert!(engine.run("(set! undefined 1)").is_err());
+}
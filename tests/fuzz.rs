@@ -0,0 +1,179 @@
+extern crate lish;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{self, Command, Output, Stdio};
+use std::thread;
+
+use lish::engine::Engine;
+
+/// Xorshift, so runs are reproducible without pulling in a random crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+const TOKENS: &[&str] = &[
+    "(", "(", "(", ")", ")", ")", "[", "]", "{", "}", "@",
+    "def", "set!", "do", "fn", "eval", "export",
+    "+", "-", "list", "vector", "first", "rest", "nth", "count", "empty?", "cons",
+    "concat", "reverse", "sort", "range", "map", "filter", "reduce", "apply",
+    "string-append", "keyword", "name", "hash", "hash-set", "get", "contains?",
+    "keys", "vals", "dissoc", "merge", "update", "atom", "deref", "reset!", "swap!",
+    "read", "require", "dir-stack", "->", "->>", "to-json", "from-json", "lines", "dir?",
+    "a", "b", "x", "ns/x", "/", "nil", "true", "false",
+    "0", "1", "-1", "42", "9223372036854775807", "-9223372036854775808", "99999999999999999999",
+    "\"\"", "\"a\"", "\"(\"", "\"\\\"\"", ":a", ":", "\\a", "\\space", "\\",
+];
+
+/// Shell syntax and builtins, leaving out the ones that change directory or
+/// replace the process.
+const SHELL_TOKENS: &[&str] = &[
+    "echo", "true", "false", "pwd", "type", "which", "alias", "unalias", "history",
+    "export", "unset", "command", "dirs", "popd",
+    "|", "&&", ";", "<", ">", ">>", "<<", "<<<", "<(", ">(", "$(", "`", ")",
+    "'", "\"", "$a", "$?", "${a}", "a=1", "a=$(echo", "{a,b}", "{1..3}", "{9..-9..2}", "EOF",
+];
+
+const CHARS: &[u8] = b"()[]{}\"\\:@;'`,~#^$%&*-+=/?!<>|. \n0123456789abcxyz";
+
+fn random_input(rng: &mut Rng) -> String {
+    random_words(rng, String::from("("), &[TOKENS])
+}
+
+/// A line that is run as shell commands unless it happens to start with `(`.
+fn random_shell_input(rng: &mut Rng) -> String {
+    random_words(rng, String::new(), &[SHELL_TOKENS, TOKENS])
+}
+
+fn random_words(rng: &mut Rng, mut input: String, vocabularies: &[&[&str]]) -> String {
+    for _ in 0..rng.below(24) {
+        if rng.below(4) == 0 {
+            input.push(CHARS[rng.below(CHARS.len())] as char);
+        } else {
+            let tokens = vocabularies[rng.below(vocabularies.len())];
+
+            input.push_str(tokens[rng.below(tokens.len())]);
+            input.push(' ');
+        }
+    }
+
+    input
+}
+
+#[test]
+fn random_lisp_input_never_panics() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..20 {
+        let mut engine = Engine::new();
+
+        for _ in 0..100 {
+            let input = random_input(&mut rng);
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let _ = engine.run(&input);
+            }));
+
+            assert!(result.is_ok(), "panicked on input: {:?}", input);
+        }
+    }
+}
+
+/// Runs lines through a lish process, in `dir` and with no `$PATH`, so that
+/// they can only run builtins and lisp.
+fn run_lish(dir: &Path, inputs: &[String]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lish"))
+        .arg("--norc")
+        .current_dir(dir)
+        .env("PATH", "")
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir)
+        .env("XDG_DATA_HOME", dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = inputs.join("\n") + "\n";
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output().unwrap();
+    let _ = writer.join();
+
+    output
+}
+
+/// Runs random lines through a lish process, in an empty directory.
+#[test]
+fn random_shell_input_never_panics() {
+    let dir = env::temp_dir().join(format!("lish-fuzz-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..20 {
+        let inputs: Vec<String> = (0..100).map(|_| random_shell_input(&mut rng)).collect();
+
+        let output = run_lish(&dir, &inputs);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(output.status.code().is_some() && !stderr.contains("panicked"), "failed on one of: {:#?}\n{}", inputs, stderr);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Deeply nested input fails with an error instead of overflowing the stack,
+/// which aborts the process rather than panicking, so the shell must still
+/// be running to echo the last line.
+#[test]
+fn deep_nesting_never_overflows() {
+    let dir = env::temp_dir().join(format!("lish-fuzz-deep-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let depth = 100_000;
+    let nested = |open: &str, close: &str| format!("{}{}", open.repeat(depth), close.repeat(depth));
+
+    let inputs = vec![
+        nested("(", ")"),
+        format!("(do {})", nested("[", "]")),
+        format!("(do {})", nested("{", "}")),
+        format!("(do {}x)", "@".repeat(depth)),
+        format!("(from-json \"{}\")", "[".repeat(depth)),
+        "(do (def f (fn (n) (f n))) (f 1))".to_owned(),
+        "(do (def g (fn (x) (map g (list x)))) (g 1))".to_owned(),
+        format!("echo {}", nested("$(", ")")),
+        format!("echo {}", nested("\"$(", ")\"")),
+        format!("echo {}", nested("<(", ")")),
+        format!("echo {}", "`".repeat(depth)),
+        format!("echo {}", nested("{a,", "}")),
+        format!("echo {}", "{a,b}".repeat(depth)),
+        format!("echo {}", "${".repeat(depth)),
+    ];
+
+    for input in &inputs {
+        let output = run_lish(&dir, &[input.clone(), "echo alive".to_owned()]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(output.status.code().is_some() && stdout.ends_with("alive\n"), "failed on {:.40}...\n{}", input, stderr);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -33,3 +33,72 @@ fn assoc() {
     assert_eq!(run(&mut engine, "(display h1)"), "\"{}\"");
     assert_eq!(run(&mut engine, "(display h2)"), "\"{ \"a\" 1 }\"");
 }
+
+#[test]
+fn print_sorted_by_key() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(hash \"b\" 2 \"a\" 1 3 \"c\")"), "{ 3 \"c\" \"a\" 1 \"b\" 2 }");
+}
+
+#[test]
+fn literal() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(do {\"a\" (+ 1 2) 2 []})"), "{ 2 [] \"a\" 3 }");
+    assert_eq!(run(&mut engine, "(do {})"), "{}");
+}
+
+#[test]
+fn get() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def h {\"a\" 1 2 \"b\"})");
+
+    assert_eq!(run(&mut engine, "(get h \"a\")"), "1");
+    assert_eq!(run(&mut engine, "(get h 2)"), "\"b\"");
+    assert_eq!(run(&mut engine, "(get h \"c\")"), "nil");
+    assert_eq!(run(&mut engine, "(get h \"c\" 0)"), "0");
+    assert_eq!(run(&mut engine, "(get nil \"a\")"), "nil");
+}
+
+#[test]
+fn contains() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(contains? {\"a\" nil} \"a\")"), "true");
+    assert_eq!(run(&mut engine, "(contains? {\"a\" nil} \"b\")"), "false");
+}
+
+#[test]
+fn keys_and_vals() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def h {\"b\" 2 \"a\" 1})");
+
+    assert_eq!(run(&mut engine, "(keys h)"), "(\"a\" \"b\")");
+    assert_eq!(run(&mut engine, "(vals h)"), "(1 2)");
+}
+
+#[test]
+fn dissoc_and_merge() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(dissoc {\"a\" 1 \"b\" 2 \"c\" 3} \"a\" \"c\")"), "{ \"b\" 2 }");
+    assert_eq!(run(&mut engine, "(merge {\"a\" 1 \"b\" 2} nil {\"b\" 3})"), "{ \"a\" 1 \"b\" 3 }");
+}
+
+#[test]
+fn update() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(update {\"a\" 1} \"a\" + 10)"), "{ \"a\" 11 }");
+    assert_eq!(run(&mut engine, "(update {} \"a\" (fn (v) v))"), "{ \"a\" nil }");
+}
+
+#[test]
+fn unhashable_key() {
+    let mut engine = Engine::new();
+
+    assert!(engine.run("(hash (list) 1)").is_err());
+}
@@ -0,0 +1,57 @@
+extern crate lish;
+
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+#[test]
+fn keywords_evaluate_to_themselves() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(list :a :b)"), "(:a :b)");
+}
+
+#[test]
+fn keywords_are_hash_keys() {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def opts {:verbose true \"name\" \"lish\"})");
+
+    assert_eq!(run(&mut engine, "(get opts :verbose)"), "true");
+    assert_eq!(run(&mut engine, "(contains? opts :name)"), "false");
+    assert_eq!(run(&mut engine, "(keys opts)"), "(:verbose \"name\")");
+}
+
+#[test]
+fn keywords_look_up_hash_maps() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(:a {:a 1})"), "1");
+    assert_eq!(run(&mut engine, "(:b {:a 1})"), "nil");
+    assert_eq!(run(&mut engine, "(:b {:a 1} 2)"), "2");
+    assert_eq!(run(&mut engine, "(map :a [{:a 1} {:a 2}])"), "(1 2)");
+}
+
+#[test]
+fn keyword_and_name() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(keyword \"a\")"), ":a");
+    assert_eq!(run(&mut engine, "(keyword :a)"), ":a");
+    assert_eq!(run(&mut engine, "(name :a)"), "\"a\"");
+    assert_eq!(run(&mut engine, "(name \"a\")"), "\"a\"");
+}
+
+#[test]
+fn chars() {
+    let mut engine = Engine::new();
+
+    assert_eq!(run(&mut engine, "(list \\a \\space \\()"), "(\\a \\space \\()");
+    assert_eq!(run(&mut engine, "(display \\a)"), "\"a\"");
+    assert_eq!(run(&mut engine, "(get {\\a 1} \\a)"), "1");
+}
@@ -0,0 +1,114 @@
+extern crate lish;
+
+use lish::Error;
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+
+    run(&mut engine, "(def *load-path* (list \"tests/modules\"))");
+
+    engine
+}
+
+#[test]
+fn require_module() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"git-helpers\")");
+
+    assert_eq!(run(&mut engine, "(git-helpers/branch \"master\")"), "\"refs/heads/master\"");
+}
+
+#[test]
+fn require_module_with_alias() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"git-helpers\" :as \"git\")");
+
+    assert_eq!(run(&mut engine, "(git/branch \"master\")"), "\"refs/heads/master\"");
+}
+
+#[test]
+fn require_module_by_path() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"./tests/modules/git-helpers.lish\" :as \"git\")");
+
+    assert_eq!(run(&mut engine, "(git/branch \"master\")"), "\"refs/heads/master\"");
+}
+
+#[test]
+fn require_loads_module_once() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"git-helpers\")");
+    run(&mut engine, "(swap! git-helpers/loads + 1)");
+    run(&mut engine, "(require \"git-helpers\" :as \"git\")");
+
+    assert_eq!(run(&mut engine, "(deref git/loads)"), "2");
+}
+
+#[test]
+fn require_only_shares_exports() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"git-helpers\")");
+
+    assert_eq!(run(&mut engine, "(sort (keys git-helpers))"), "(\"branch\" \"loads\")");
+    assert!(engine.run("(do git-helpers/prefix)").is_err());
+    assert!(engine.run("(do prefix)").is_err());
+}
+
+#[test]
+fn require_hides_user_globals() {
+    let mut engine = engine();
+    engine.load_standard_library().unwrap();
+
+    run(&mut engine, "(def secret \"user\")");
+    run(&mut engine, "(require \"isolated\")");
+
+    assert_eq!(run(&mut engine, "(isolated/secret-visible?)"), "false");
+    assert_eq!(run(&mut engine, "(isolated/second-name)"), "\"stdlib\"");
+}
+
+#[test]
+fn require_missing_module() {
+    let mut engine = engine();
+
+    assert!(engine.run("(require \"missing\")").is_err());
+}
+
+#[test]
+fn require_circular_module() {
+    let mut engine = engine();
+
+    assert!(engine.run("(require \"cycle\")").is_err());
+}
+
+#[test]
+fn require_relative_to_module() {
+    let mut engine = engine();
+
+    run(&mut engine, "(require \"tests/modules/nested/outer.lish\" :as \"outer\")");
+
+    assert_eq!(run(&mut engine, "(outer/greeting)"), "\"hello inner\"");
+}
+
+#[test]
+fn export_outside_module() {
+    let mut engine = engine();
+
+    match engine.run("(export x)") {
+        Err(Error::BadSyntax(..)) => {},
+        other => panic!("expected bad syntax, got {:?}", other),
+    }
+    assert!(engine.run("(do *exports*)").is_err());
+}
@@ -0,0 +1,64 @@
+extern crate lish;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use lish::Error;
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+fn startup_file(name: &str, contents: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("lish-startup-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(name);
+    File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+
+    path
+}
+
+#[test]
+fn load_startup_file() {
+    let path = startup_file("init.lish", "(def a 1)\n\n(def b\n  (+ a 1))\n");
+    let mut engine = Engine::new();
+
+    engine.load_startup_file(&path).unwrap();
+
+    assert_eq!(run(&mut engine, "(+ a b)"), "3");
+}
+
+#[test]
+fn report_startup_file_error_location() {
+    let path = startup_file("error.lish", "(def a 1)\n\n(def b\n  (+ a c))\n(def d 4)\n");
+    let mut engine = Engine::new();
+
+    match engine.load_startup_file(&path) {
+        Err(Error::LoadError(file, line, error)) => {
+            assert_eq!(file, path.display().to_string());
+            assert_eq!(line, Some(3));
+            assert!(match *error { Error::UndefinedBinding(ref name) => name == "c", _ => false });
+        },
+        _ => panic!("expected a load error"),
+    }
+
+    assert_eq!(run(&mut engine, "(do a)"), "1");
+    assert!(engine.run("(do d)").is_err());
+}
+
+#[test]
+fn report_missing_startup_file() {
+    let mut engine = Engine::new();
+
+    match engine.load_startup_file(&PathBuf::from("/nonexistent/init.lish")) {
+        Err(Error::LoadError(_, None, _)) => {},
+        _ => panic!("expected a load error"),
+    }
+}
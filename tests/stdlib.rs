@@ -0,0 +1,41 @@
+extern crate lish;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+
+use lish::engine::Engine;
+use lish::printer::Printer;
+
+fn run(engine: &mut Engine, input: &str) -> String {
+    let printer = Printer::new();
+
+    printer.print(&engine.run(input).unwrap().unwrap(), true)
+}
+
+#[test]
+fn load_embedded_standard_library() {
+    let mut engine = Engine::new();
+
+    engine.load_standard_library_from(None).unwrap();
+
+    assert_eq!(run(&mut engine, "(second (list 1 2 3))"), "2");
+    assert_eq!(run(&mut engine, "(last (list 1 2 3))"), "3");
+    assert_eq!(run(&mut engine, "(do load-file)"), "#<function ...>");
+}
+
+#[test]
+fn override_standard_library_modules() {
+    let dir = env::temp_dir().join(format!("lish-stdlib-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    File::create(dir.join("list.lish")).unwrap().write_all(b"(def last (fn (coll) \"overridden\"))").unwrap();
+
+    let mut engine = Engine::new();
+
+    engine.load_standard_library_from(Some(&dir)).unwrap();
+
+    assert_eq!(run(&mut engine, "(last (list 1 2 3))"), "\"overridden\"");
+    assert_eq!(run(&mut engine, "(do load-file)"), "#<function ...>");
+
+    fs::remove_dir_all(&dir).unwrap();
+}